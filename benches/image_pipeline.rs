@@ -0,0 +1,105 @@
+//! Benchmarks for the image pipeline behind target choice embeds
+//!
+//! The bot is a binary crate, so the pipeline's modules are included directly, the same way
+//! `build.rs` includes `version_data.rs`. Run with `cargo bench --bench image_pipeline`.
+
+#[cfg(not(feature = "deterministic"))]
+#[allow(dead_code)]
+#[path = "../src/version_data.rs"]
+mod version_data;
+
+#[allow(dead_code)]
+#[path = "../src/resources.rs"]
+mod resources;
+
+#[path = "../src/helpers/compositor.rs"]
+mod compositor;
+
+mod helpers {
+    pub type Error = Box<dyn std::error::Error + Send + Sync>;
+}
+
+use compositor::Image;
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use image::Rgba;
+
+/// Discord serves avatars at 1024x1024 when asked for the largest size
+const AVATAR_SIZE: u32 = 1024;
+
+const ALIVENESSES: [bool; 6] = [true, false, true, true, false, true];
+
+fn avatars() -> Vec<Image> {
+    (0..6u8)
+        .map(|i| {
+            Image::from_pixel(
+                AVATAR_SIZE,
+                AVATAR_SIZE,
+                Rgba([i * 40, 255 - i * 40, 128, 255]),
+            )
+        })
+        .collect()
+}
+
+fn pipeline_steps(c: &mut Criterion) {
+    c.bench_function("grayscale_dead_players", |b| {
+        b.iter_batched(
+            avatars,
+            |avatars| compositor::grayscale_dead_players(avatars, black_box(&ALIVENESSES)),
+            BatchSize::LargeInput,
+        )
+    });
+
+    c.bench_function("make_background_image", |b| {
+        b.iter(|| compositor::make_background_image(black_box(&ALIVENESSES)).unwrap())
+    });
+
+    let background = compositor::make_background_image(&ALIVENESSES).unwrap();
+    c.bench_function("merge_avatars", |b| {
+        b.iter_batched(
+            || (avatars(), background.clone()),
+            |(avatars, background)| compositor::merge_avatars(avatars, background).unwrap(),
+            BatchSize::LargeInput,
+        )
+    });
+
+    let merged = compositor::merge_avatars(avatars(), background).unwrap();
+    c.bench_function("encode_to_png", |b| {
+        b.iter_batched(
+            || merged.clone(),
+            |merged| compositor::encode_to_png(merged).unwrap(),
+            BatchSize::LargeInput,
+        )
+    });
+
+    c.bench_function("fallback_avatar", |b| {
+        b.iter(|| compositor::fallback_avatar(black_box(Some("orange")), black_box(Some(3))))
+    });
+}
+
+fn whole_pipeline(c: &mut Criterion) {
+    let mut group = c.benchmark_group("render");
+    // Every run takes a while on a Raspberry Pi, the default of 100 samples would take ages
+    group.sample_size(20);
+
+    group.bench_function("six_avatars", |b| {
+        b.iter_batched(
+            avatars,
+            |avatars| compositor::render(avatars, black_box(&ALIVENESSES)).unwrap(),
+            BatchSize::LargeInput,
+        )
+    });
+
+    group.bench_function("six_fallbacks", |b| {
+        b.iter(|| {
+            let avatars = (1..7)
+                .map(|room| compositor::fallback_avatar(Some("blue"), Some(room)))
+                .collect();
+            compositor::render(avatars, black_box(&ALIVENESSES)).unwrap()
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, pipeline_steps, whole_pipeline);
+criterion_main!(benches);
@@ -28,6 +28,7 @@ fn main() -> std::io::Result<()> {
     let version_file_dest_path = out_dir.join("version.json");
 
     copy_resource_reactions(&out_dir);
+    copy_resource_role_icons(&out_dir);
     println!("?");
 
     let _ = std::fs::remove_file(&version_file_dest_path);
@@ -98,6 +99,23 @@ fn copy_resource_reactions(out_dir: &PathBuf) {
     }
 }
 
+fn copy_resource_role_icons(out_dir: &PathBuf) {
+    let cargo = env::var("CARGO_MANIFEST_DIR").expect("fuc");
+    for role in &[
+        "king",
+        "prince",
+        "the_double",
+        "sorcerer",
+        "knight",
+        "revolutionary",
+    ] {
+        let _ = std::fs::copy(
+            Path::new(&cargo).join(format!("res/{}.png", role)),
+            out_dir.join(format!("{}.png", role)),
+        );
+    }
+}
+
 #[cfg(not(feature = "deterministic"))]
 fn git_cmd() -> Command {
     let mut cmd = Command::new("git".to_string());
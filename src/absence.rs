@@ -0,0 +1,250 @@
+//! Deals with players that leave the server, or lose the player role, while their game runs
+//!
+//! They're marked as absent and the host gets to pick what happens to them: they can be killed
+//! off, replaced with `!replace`, or the game can be paused until the host makes up their mind.
+//! Games the bot hosts itself have nobody to ask, so the player is killed off right away.
+
+use crate::{
+    commands::game::{persist_game, running_game, GameContainer},
+    data,
+    game::{DeathCause, Game},
+    helpers::react::react_with,
+    i18n, shutdown, themes,
+};
+
+use futures::StreamExt;
+use serenity::{
+    collector::ReactionCollectorBuilder,
+    framework::standard::CommandResult,
+    model::{
+        guild::Member,
+        id::{GuildId, UserId},
+        misc::Mentionable,
+    },
+    prelude::*,
+};
+use std::time::Duration;
+use tracing::{info, warn, Instrument};
+
+static KILL: &str = "💀";
+static REPLACE: &str = "🔁";
+static PAUSE: &str = "⏸️";
+
+/// How often the question to the host checks whether the player was dealt with in some other way
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// What the host gets told about a player that's gone
+#[derive(Debug, Clone, Copy)]
+enum Reason {
+    LeftServer,
+    LostRole,
+    /// The player was already gone when the bot stopped
+    StillGone,
+}
+
+impl Reason {
+    fn key(self) -> &'static str {
+        match self {
+            Reason::LeftServer => "absence.ask.left_server",
+            Reason::LostRole => "absence.ask.lost_role",
+            Reason::StillGone => "absence.ask.still_gone",
+        }
+    }
+}
+
+/// Marks `user` as absent if they were playing in the running game in `guild`
+pub async fn member_left(ctx: Context, guild: GuildId, user: UserId) {
+    mark_absent(ctx, guild, user, Reason::LeftServer).await;
+}
+
+/// Marks the member as absent if they were playing and just lost the player role
+pub async fn member_updated(ctx: Context, old: Option<Member>, new: Member) {
+    let game = match ctx.data.read().await.get::<GameContainer>().cloned() {
+        Some(game) => game,
+        None => return,
+    };
+    let role = game.read().await.player_role();
+
+    let had_role = old.map_or(true, |old| old.roles.contains(&role));
+    if had_role && !new.roles.contains(&role) {
+        mark_absent(ctx, new.guild_id, new.user.id, Reason::LostRole).await;
+    }
+}
+
+/// Asks the host again about the players that were absent when the bot stopped
+pub fn ask_again(ctx: &Context, game: &Game) {
+    for user in game.absent_players() {
+        tokio::spawn(
+            ask_host(ctx.clone(), game.id(), user, Reason::StillGone).instrument(game.span()),
+        );
+    }
+}
+
+async fn mark_absent(ctx: Context, guild: GuildId, user: UserId, reason: Reason) {
+    let game = match ctx.data.read().await.get::<GameContainer>().cloned() {
+        Some(game) => game,
+        None => return,
+    };
+
+    let (id, span) = {
+        let mut game = game.write().await;
+        if game.guild() != guild || !game.is_started() || game.is_ended() {
+            return;
+        }
+        match game.player_mut(user) {
+            Some(player) if player.is_alive() && !player.is_absent() => player.set_absent(true),
+            _ => return,
+        }
+        (game.id(), game.span())
+    };
+
+    info!(parent: &span, "A player is gone: {:?}", reason);
+    persist_game(&ctx).await;
+    tokio::spawn(ask_host(ctx, id, user, reason).instrument(span));
+}
+
+async fn ask_host(ctx: Context, id: u64, user: UserId, reason: Reason) {
+    let (guild, channel, host, automated) = match running_game(&ctx, id).await {
+        Some(game) => {
+            let game = game.read().await;
+            (
+                game.guild(),
+                game.announcement_channel(),
+                game.host(),
+                game.is_automated(),
+            )
+        }
+        None => return,
+    };
+
+    if automated {
+        if let Err(err) = kill(&ctx, id, user).await {
+            warn!("Couldn't kill off a player that's gone: {}", err);
+        }
+        return;
+    }
+
+    let locale = i18n::locale(&ctx, Some(guild), Some(host)).await;
+    let msg = channel
+        .say(
+            &ctx,
+            i18n::tr_with(
+                &locale,
+                reason.key(),
+                &[
+                    ("host", &host.mention()),
+                    ("player", &user.mention()),
+                    ("kill", &KILL),
+                    ("replace", &REPLACE),
+                    ("pause", &PAUSE),
+                ],
+            ),
+        )
+        .await;
+    let msg = match msg {
+        Ok(msg) => msg,
+        Err(err) => {
+            warn!("Couldn't ask the host about a player that's gone: {}", err);
+            return;
+        }
+    };
+
+    // Collecting from before the reactions are added, so none of the host's get lost
+    let shutdown = shutdown::get(&ctx).await;
+    let mut reactions = ReactionCollectorBuilder::new(&ctx)
+        .message_id(msg.id)
+        .author_id(host)
+        .filter(|r| [KILL, REPLACE, PAUSE].contains(&r.emoji.to_string().as_str()))
+        .await;
+    if let Err(err) = react_with(&ctx, &msg, &[KILL, REPLACE, PAUSE]).await {
+        warn!("{}", err);
+    }
+
+    loop {
+        let reaction = tokio::select! {
+            reaction = reactions.next() => match reaction {
+                Some(reaction) => Some(reaction),
+                None => return,
+            },
+            _ = tokio::time::sleep(CHECK_INTERVAL) => None,
+            // The player stays marked as absent, so the host is asked again once the bot is back
+            _ = shutdown.wait() => return,
+        };
+
+        // The host may have used `!replace` or ended the game without reacting
+        if !is_absent(&ctx, id, user).await {
+            return;
+        }
+        let reaction = match reaction {
+            Some(reaction) => reaction,
+            None => continue,
+        };
+        let emoji = &reaction.as_inner_ref().emoji;
+
+        let reply = if emoji.unicode_eq(KILL) {
+            match kill(&ctx, id, user).await {
+                Ok(()) => return,
+                Err(err) => i18n::tr_with(&locale, "absence.kill_failed", &[("error", &err)]),
+            }
+        } else {
+            pause(&ctx, id).await;
+            if emoji.unicode_eq(REPLACE) {
+                let prefix = data::prefix(&ctx, Some(guild)).await;
+                i18n::tr_with(
+                    &locale,
+                    "absence.paused_replace",
+                    &[("prefix", &prefix), ("player", &user.mention())],
+                )
+            } else {
+                i18n::tr_with(
+                    &locale,
+                    "absence.paused",
+                    &[("kill", &KILL), ("replace", &REPLACE)],
+                )
+            }
+        };
+
+        if let Err(err) = channel.say(&ctx, reply).await {
+            warn!("{}", err);
+        }
+    }
+}
+
+async fn is_absent(ctx: &Context, id: u64, user: UserId) -> bool {
+    match running_game(ctx, id).await {
+        Some(game) => game.read().await.absent_players().contains(&user),
+        None => false,
+    }
+}
+
+async fn pause(ctx: &Context, id: u64) {
+    if let Some(game) = running_game(ctx, id).await {
+        game.write().await.pause();
+        persist_game(ctx).await;
+    }
+}
+
+async fn kill(ctx: &Context, id: u64, user: UserId) -> CommandResult {
+    let game = match running_game(ctx, id).await {
+        Some(game) => game,
+        None => return Ok(()),
+    };
+
+    {
+        let mut game = game.write().await;
+        let theme = themes::get(ctx, game.theme()).await;
+        let channel = game.announcement_channel();
+        match game.player_mut(user) {
+            Some(player) if player.is_alive() => {
+                player
+                    .set_dead(DeathCause::Absence, ctx, channel, &theme)
+                    .await?
+            }
+            _ => return Ok(()),
+        }
+        game.resume_if_everyone_is_back();
+    }
+    persist_game(ctx).await;
+
+    Ok(())
+}
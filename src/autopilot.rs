@@ -0,0 +1,170 @@
+//! Games the bot hosts itself, made with `!autogame`
+//!
+//! Nobody has to drive them: the bot starts the game as soon as six players joined, or at the time
+//! it was scheduled for if there's enough of them by then, moves it on to the next time block every
+//! `rules.block_minutes`, and cleans up after it once it's over. Decisions that aren't made in
+//! time get the rules' defaults, like in any other game.
+//!
+//! When the next step is due is kept on the game, see `Game::next_step`, so the bot can carry on
+//! where it left off after a restart.
+
+use crate::{
+    commands::game::{abandon_game, finish_game, persist_game, running_game, start_game},
+    game::Game,
+    i18n, shutdown,
+};
+
+use chrono::{DateTime, Utc};
+use serenity::{model::id::ChannelId, prelude::*};
+use std::{fmt::Display, time::Duration};
+use tracing::{info, warn, Instrument};
+
+/// How often a lobby that starts once it's full checks whether it is
+const LOBBY_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+/// How many times cleaning up after a game is tried before it's given up on
+const CLEAN_UP_ATTEMPTS: u32 = 3;
+
+/// What happens to a game after it took a step
+enum After {
+    /// It waits for its next step
+    Continue,
+    /// It's over, or it couldn't start, so everything made for it gets cleaned up
+    CleanUp,
+    /// It's not the running game anymore, e.g. because someone ended it with `!endgame`
+    Stop,
+}
+
+/// Drives `game` in the background for as long as it's the running game
+pub fn spawn(ctx: Context, game: &Game) {
+    tokio::spawn(drive(ctx, game.id()).instrument(game.span()));
+}
+
+async fn drive(ctx: Context, id: u64) {
+    let shutdown = shutdown::get(&ctx).await;
+    loop {
+        let game = match running_game(&ctx, id).await {
+            Some(game) => game,
+            None => return,
+        };
+        let wait = match game.read().await.next_step() {
+            Some(at) => (at - Utc::now()).to_std().unwrap_or_default(),
+            None => LOBBY_CHECK_INTERVAL,
+        };
+
+        tokio::select! {
+            _ = tokio::time::sleep(wait) => {}
+            // The game was saved as it is, and gets picked up again once the bot is back
+            _ = shutdown.wait() => return,
+        }
+
+        match take_step(&ctx, id).await {
+            After::Continue => persist_game(&ctx).await,
+            After::CleanUp => {
+                clean_up(&ctx, id).await;
+                return;
+            }
+            After::Stop => return,
+        }
+    }
+}
+
+/// Cleans up after the game, giving up on it after a few tries so it doesn't keep other games
+/// from being made
+async fn clean_up(ctx: &Context, id: u64) {
+    info!("Cleaning up after the game");
+    for attempt in 1..=CLEAN_UP_ATTEMPTS {
+        if running_game(ctx, id).await.is_none() {
+            return;
+        }
+        match finish_game(ctx).await {
+            Ok(_) => return,
+            Err(err) => warn!(
+                "Couldn't clean up after the game (attempt {}): {}",
+                attempt, err
+            ),
+        }
+        tokio::time::sleep(LOBBY_CHECK_INTERVAL).await;
+    }
+
+    warn!("Giving up on cleaning up after the game, it's left for when the bot restarts");
+    abandon_game(ctx, id).await;
+}
+
+/// Starts the game if it's due to start, or moves it on to the next time block
+async fn take_step(ctx: &Context, id: u64) -> After {
+    let game = match running_game(ctx, id).await {
+        Some(game) => game,
+        None => return After::Stop,
+    };
+    // Whoever holds the game may be waiting on ctx.data, so this is looked up before locking it
+    let guild = game.read().await.guild();
+    let locale = i18n::locale(ctx, Some(guild), None).await;
+    let mut game = game.write().await;
+    let channel = game.announcement_channel();
+
+    if game.is_ended() {
+        return After::CleanUp;
+    }
+
+    if game.is_started() {
+        *game = game.clone().transition_to_next_state(ctx).await;
+    } else if game.can_start() {
+        match start_game(ctx, &mut game, channel).await {
+            Ok(true) => {}
+            Ok(false) => {
+                announce(ctx, channel, i18n::tr(&locale, "autopilot.called_off")).await;
+                return After::CleanUp;
+            }
+            Err(err) => {
+                announce(
+                    ctx,
+                    channel,
+                    i18n::tr_with(&locale, "autopilot.start_failed", &[("error", &err)]),
+                )
+                .await;
+                return After::CleanUp;
+            }
+        }
+    } else if game.next_step().is_some() {
+        announce(
+            ctx,
+            channel,
+            i18n::tr(&locale, "autopilot.not_enough_players"),
+        )
+        .await;
+        return After::CleanUp;
+    } else {
+        return After::Continue;
+    }
+
+    let block = game.rules().block_length();
+    if game.is_ended() {
+        announce(
+            ctx,
+            channel,
+            i18n::tr_with(
+                &locale,
+                "autopilot.game_over",
+                &[("minutes", &game.rules().block_minutes)],
+            ),
+        )
+        .await;
+    }
+    game.set_next_step(from_now(block));
+
+    After::Continue
+}
+
+fn from_now(duration: Duration) -> DateTime<Utc> {
+    let now = Utc::now();
+    chrono::Duration::from_std(duration)
+        .ok()
+        .and_then(|duration| now.checked_add_signed(duration))
+        .unwrap_or(chrono::MAX_DATETIME)
+}
+
+async fn announce(ctx: &Context, channel: ChannelId, text: impl Display) {
+    if let Err(err) = channel.say(ctx, text).await {
+        warn!("Couldn't announce something about the game: {}", err);
+    }
+}
@@ -1,7 +1,14 @@
 use crate::{
     commands::{help::*, *},
-    data::{stats, Cdn, Prefix, Reqwest, ReqwestClient},
+    config::{Config, FeaturesContainer},
+    data::{stats, Prefix, Reqwest, ReqwestClient},
     hooks::*,
+    shutdown::{Shutdown, ShutdownContainer},
+    storage::{
+        AvatarCache, AvatarCacheContainer, GuildConfig, GuildConfigContainer, GuildConfigs,
+        Storage, StorageContainer, UserLocaleContainer, UserLocales,
+    },
+    themes::{ThemeContainer, Themes},
 };
 use serenity::{
     async_trait,
@@ -9,24 +16,72 @@ use serenity::{
         bridge::gateway::{GatewayIntents, ShardManager},
         ClientBuilder,
     },
-    framework::standard::StandardFramework,
+    framework::{standard::StandardFramework, Framework},
     http::Http,
     model::{
+        channel::Message,
         gateway::Ready,
-        id::{ChannelId, UserId},
+        guild::Member,
+        id::{GuildId, UserId},
+        user::User,
     },
     prelude::*,
 };
-use std::{collections::HashSet, fs::File, io::Read, sync::Arc, time};
+use std::{
+    collections::HashSet,
+    fs::File,
+    io::Read,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time,
+};
 use tokio::sync::Mutex;
-use tracing::info;
+use tracing::{error, info, warn, Instrument};
 
-struct Handler;
+struct Handler {
+    /// Whether to deal with the games that were running when the bot stopped
+    recovery: bool,
+    /// `ready` fires again on reconnects, but unfinished games should be dealt with only once
+    offered_recovery: AtomicBool,
+}
 
 #[async_trait]
 impl EventHandler for Handler {
-    async fn ready(&self, _: Context, ready: Ready) {
+    async fn ready(&self, ctx: Context, ready: Ready) {
         info!("{} is connected!", ready.user.name);
+        crate::systemd::ready();
+
+        if self.recovery && !self.offered_recovery.swap(true, Ordering::SeqCst) {
+            tokio::spawn(crate::recovery::offer_recovery(ctx));
+        }
+    }
+
+    async fn guild_member_removal(
+        &self,
+        ctx: Context,
+        guild: GuildId,
+        user: User,
+        _: Option<Member>,
+    ) {
+        crate::absence::member_left(ctx, guild, user.id).await;
+    }
+
+    async fn guild_member_update(&self, ctx: Context, old: Option<Member>, new: Member) {
+        crate::absence::member_updated(ctx, old, new).await;
+    }
+}
+
+/// Runs every command inside the span of the game running where it was sent, so everything the
+/// command logs ends up in the game's history
+struct InGameSpan(StandardFramework);
+
+#[async_trait]
+impl Framework for InGameSpan {
+    async fn dispatch(&self, ctx: Context, msg: Message) {
+        let span = crate::commands::game::game_span(&ctx, msg.guild_id).await;
+        self.0.dispatch(ctx, msg).instrument(span).await;
     }
 }
 
@@ -41,20 +96,18 @@ pub struct Bot {
 }
 
 impl Bot {
-    pub async fn new(
-        token: String,
-        prefix: String,
-        cdn_channel_id: ChannelId,
-        startup_time: time::Instant,
-    ) -> Self {
-        let http = Http::new_with_token(&token);
-        let (owners, bot_id) = Self::application_info(&http).await;
-        let framework = Self::new_framework(&prefix, bot_id, owners).await;
-        let client = Self::new_client(&token, http, framework).await;
+    pub async fn new(config: Config, storage: Storage, startup_time: time::Instant) -> Self {
+        let http = Http::new_with_token(&config.token);
+        let (mut owners, bot_id) = Self::application_info(&http).await;
+        if !config.owners.is_empty() {
+            owners = config.owners.clone();
+        }
+        let framework = Self::new_framework(bot_id, owners).await;
+        let client =
+            Self::new_client(&config.token, http, framework, config.features.recovery).await;
 
         let mut bot = Self { client };
-        bot.initialise_data(cdn_channel_id, prefix, startup_time)
-            .await;
+        bot.initialise_data(config, storage, startup_time).await;
 
         bot
     }
@@ -67,16 +120,23 @@ impl Bot {
         Arc::clone(&self.client.shard_manager)
     }
 
-    async fn new_framework(
-        prefix: &str,
-        bot_id: UserId,
-        owners: HashSet<UserId>,
-    ) -> StandardFramework {
+    pub fn data(&self) -> Arc<RwLock<TypeMap>> {
+        Arc::clone(&self.client.data)
+    }
+
+    pub fn http(&self) -> Arc<Http> {
+        Arc::clone(&self.client.cache_and_http.http)
+    }
+
+    async fn new_framework(bot_id: UserId, owners: HashSet<UserId>) -> StandardFramework {
         StandardFramework::new()
             .configure(|c| {
                 c.with_whitespace(true)
                     .on_mention(Some(bot_id))
-                    .prefix(&prefix)
+                    // Every prefix comes from `dynamic_prefix`, so guilds that set their own with
+                    // `!config` don't answer to the global one too
+                    .prefix("")
+                    .dynamic_prefix(dynamic_prefix)
                     .delimiters(vec![" "])
                     // Sets the bot's owners. These will be used for commands that
                     // are owners only.
@@ -98,19 +158,28 @@ impl Bot {
             .group(&ITEMINTERACTIONS_GROUP)
             .group(&PLAYERINTERACTIONS_GROUP)
             .group(&GAMEINFORMATION_GROUP)
+            .group(&CONFIGURATION_GROUP)
     }
 
-    async fn new_client(token: &str, http: Http, framework: StandardFramework) -> Client {
+    async fn new_client(
+        token: &str,
+        http: Http,
+        framework: StandardFramework,
+        recovery: bool,
+    ) -> Client {
         ClientBuilder::new_with_http(http)
             .token(token)
-            .event_handler(Handler)
+            .event_handler(Handler {
+                recovery,
+                offered_recovery: AtomicBool::new(false),
+            })
             .intents(
                 GatewayIntents::GUILD_MEMBERS
                     | GatewayIntents::GUILD_MESSAGES
                     | GatewayIntents::GUILD_MESSAGE_REACTIONS
                     | GatewayIntents::GUILDS,
             )
-            .framework(framework)
+            .framework(InGameSpan(framework))
             .await
             .expect("Err creating client")
     }
@@ -136,8 +205,8 @@ impl Bot {
 
     async fn initialise_data(
         &mut self,
-        cdn_channel_id: ChannelId,
-        prefix: String,
+        config: Config,
+        storage: Storage,
         startup_time: time::Instant,
     ) {
         let reqwest_client = Reqwest::builder()
@@ -147,6 +216,36 @@ impl Bot {
                 "Could not create a Reqwest client, which is necessary for the bot to function.",
             );
 
+        let defaults = GuildConfig {
+            rules: config.rules,
+            ..GuildConfig::default()
+        };
+        let guild_configs = GuildConfigs::load(&storage, defaults).unwrap_or_else(|err| {
+            error!("Could not load the guild configurations: {}", err);
+            panic!("Refusing to start and potentially overwrite the guild configurations");
+        });
+        let user_locales = UserLocales::load(&storage).unwrap_or_else(|err| {
+            error!("Could not load the users' locales: {}", err);
+            panic!("Refusing to start and potentially overwrite the users' locales");
+        });
+        // Without the cache avatars are just downloaded every time, so the bot can still work
+        let avatar_cache = if config.features.avatar_cache {
+            match AvatarCache::open(&storage, config.images.avatar_cache_size()) {
+                Ok(cache) => {
+                    if let Err(err) = cache.prune() {
+                        warn!("Could not remove old avatars from the cache: {}", err);
+                    }
+                    Some(cache)
+                }
+                Err(err) => {
+                    error!("Could not open the avatar cache: {}", err);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
         let mut data = self.client.data.write().await;
 
         #[cfg(target_os = "linux")]
@@ -157,9 +256,17 @@ impl Bot {
         data.insert::<stats::CommandStatisticsContainer>(Default::default());
         data.insert::<stats::StartupTime>(startup_time);
         data.insert::<ShardManagerContainer>(Arc::clone(&self.client.shard_manager));
+        data.insert::<ShutdownContainer>(Arc::new(Shutdown::default()));
         data.insert::<ReqwestClient>(reqwest_client);
-        data.insert::<Cdn>(cdn_channel_id);
-        data.insert::<Prefix>(prefix);
+        if let Some(cache) = avatar_cache {
+            data.insert::<AvatarCacheContainer>(Arc::new(cache));
+        }
+        data.insert::<Prefix>(config.prefix);
+        data.insert::<FeaturesContainer>(config.features);
+        data.insert::<ThemeContainer>(Arc::new(Themes::load(&storage)));
+        data.insert::<StorageContainer>(Arc::new(storage));
+        data.insert::<GuildConfigContainer>(Arc::new(RwLock::new(guild_configs)));
+        data.insert::<UserLocaleContainer>(Arc::new(RwLock::new(user_locales)));
     }
 }
 
@@ -0,0 +1,221 @@
+//! The command line of the bot's binary, and the commands that work without connecting to Discord
+
+use crate::{
+    config::{Config, Problem},
+    game::{
+        simulation::{self, Death},
+        RoleHolder, RoleName,
+    },
+    storage::{self, GuildConfig, GuildConfigs, Storage},
+};
+
+use rand::{rngs::StdRng, SeedableRng};
+use serenity::{framework::standard::CommandResult, model::id::GuildId};
+use std::{fs, path::PathBuf};
+use structopt::StructOpt;
+
+#[derive(StructOpt, Debug)]
+#[structopt(about = "A Discord bot for playing Kingdom Royale")]
+pub struct Opt {
+    /// The configuration file to use instead of maid.toml or $MAID_CONFIG
+    #[structopt(long, global = true, parse(from_os_str))]
+    pub config: Option<PathBuf>,
+    /// What to log, e.g. `info` or `kingdom_royale_maid=debug`, instead of the configured level
+    #[structopt(long, global = true)]
+    pub log_level: Option<String>,
+    #[structopt(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(StructOpt, Debug)]
+pub enum Command {
+    /// Connects to Discord and runs the bot, which is what happens without a command
+    Run,
+    /// Lists everything that's wrong with the configuration
+    CheckConfig,
+    /// Rewrites the storage directory in the format this version of the bot uses
+    ///
+    /// The bot shouldn't be running, or it may overwrite the files again.
+    Migrate,
+    /// Prints a game kept in storage as JSON
+    ExportGame {
+        /// The ID of the game, as shown in the bot's logs
+        id: u64,
+        /// Where to write the game instead of printing it
+        #[structopt(long, short, parse(from_os_str))]
+        output: Option<PathBuf>,
+    },
+    /// Plays games offline, with every decision made at random, and shows how they went
+    Simulate {
+        /// How many games to play
+        #[structopt(long, default_value = "1000")]
+        games: u32,
+        /// Makes the games the same every time
+        #[structopt(long)]
+        seed: Option<u64>,
+    },
+    /// Deletes everything kept about a server, its settings and its games
+    ///
+    /// The bot shouldn't be running, or it may write them back.
+    PurgeGuildData {
+        /// The ID of the server
+        guild: u64,
+        /// Actually delete them, instead of only listing what would be deleted
+        #[structopt(long)]
+        yes: bool,
+    },
+}
+
+impl Command {
+    /// Whether the command talks to whoever runs it on stdout, so logs should go elsewhere
+    pub fn prints_output(&self) -> bool {
+        !matches!(self, Command::Run)
+    }
+
+    /// Whether the command connects to Discord, so the configuration has to have a token
+    pub fn needs_token(&self) -> bool {
+        matches!(self, Command::Run | Command::CheckConfig)
+    }
+}
+
+pub fn check_config(config: Result<Config, Vec<Problem>>) -> CommandResult {
+    match config {
+        Ok(_) => {
+            println!("The configuration is valid");
+            Ok(())
+        }
+        Err(problems) => {
+            for problem in &problems {
+                eprintln!("{}", problem);
+            }
+            Err(format!("Found {} problems in the configuration", problems.len()).into())
+        }
+    }
+}
+
+pub fn migrate(config: &Config) -> CommandResult {
+    let storage = Storage::open(&config.storage_path)?;
+    let migrated = storage::migrate(&storage)?;
+
+    if migrated.is_empty() {
+        println!("There was nothing to migrate");
+    }
+    for name in migrated {
+        println!("Migrated {}", name);
+    }
+
+    Ok(())
+}
+
+pub fn export_game(config: &Config, id: u64, output: Option<PathBuf>) -> CommandResult {
+    let storage = Storage::open(&config.storage_path)?;
+    let record = storage::load_game(&storage, id)?
+        .ok_or_else(|| format!("There's no game with the ID {}", id))?;
+    let json = serde_json::to_string_pretty(&record)?;
+
+    match output {
+        Some(path) => fs::write(path, json)?,
+        None => println!("{}", json),
+    }
+
+    Ok(())
+}
+
+pub fn simulate(config: &Config, games: u32, seed: Option<u64>) -> CommandResult {
+    if games == 0 {
+        return Err("There needs to be at least one game to simulate".into());
+    }
+
+    let seed = seed.unwrap_or_else(rand::random);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let roles: Vec<RoleName> = RoleHolder::all().iter().map(RoleHolder::name).collect();
+    let mut wins = vec![0u32; roles.len()];
+    let mut deaths = [0u32; 3];
+    let mut days = 0u64;
+    let mut nobody_won = 0u32;
+
+    for _ in 0..games {
+        let outcome = simulation::simulate(&config.rules, &mut rng);
+
+        days += u64::from(outcome.day) + 1;
+        if outcome.winners.is_empty() {
+            nobody_won += 1;
+        }
+        for winner in outcome.winners {
+            if let Some(idx) = roles.iter().position(|role| *role == winner) {
+                wins[idx] += 1;
+            }
+        }
+        for (_, _, death) in outcome.deaths {
+            deaths[death as usize] += 1;
+        }
+    }
+
+    let percent = |count: u32| f64::from(count) * 100.0 / f64::from(games);
+    let per_game = |count: u32| f64::from(count) / f64::from(games);
+
+    println!("Simulated {} games with the seed {}", games, seed);
+    println!(
+        "Games lasted {:.1} days on average",
+        days as f64 / f64::from(games)
+    );
+    println!("Nobody survived {:.1}% of them", percent(nobody_won));
+
+    println!("\nWin rates:");
+    for (role, count) in roles.iter().zip(wins) {
+        println!("  {:<14} {:>5.1}%", role.to_string(), percent(count));
+    }
+
+    println!("\nDeaths per game:");
+    for (death, name) in [
+        (Death::Murder, "Murder"),
+        (Death::Assassination, "Assassination"),
+        (Death::Starvation, "Starvation"),
+    ]
+    .iter()
+    {
+        println!("  {:<14} {:>5.2}", name, per_game(deaths[*death as usize]));
+    }
+
+    Ok(())
+}
+
+pub fn purge_guild_data(config: &Config, guild: GuildId, yes: bool) -> CommandResult {
+    let storage = Storage::open(&config.storage_path)?;
+    let mut configs = GuildConfigs::load(&storage, GuildConfig::default())?;
+
+    let has_config = configs.contains(guild);
+    let games: Vec<u64> = storage::all_games(&storage)?
+        .into_iter()
+        .filter(|record| record.game.guild() == guild)
+        .map(|record| record.game.id())
+        .collect();
+
+    if !has_config && games.is_empty() {
+        println!("There's nothing kept about {}", guild);
+        return Ok(());
+    }
+
+    let action = if yes { "Deleting" } else { "Would delete" };
+    if has_config {
+        println!("{} the settings of {}", action, guild);
+    }
+    for id in &games {
+        println!("{} game {}", action, id);
+    }
+
+    if !yes {
+        println!("Run this again with --yes to delete them");
+        return Ok(());
+    }
+
+    if has_config {
+        configs.remove(&storage, guild)?;
+    }
+    for id in games {
+        storage::delete_game(&storage, id)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,400 @@
+use super::prelude::*;
+use crate::{
+    data,
+    game::{AssistantDefault, TargetDefault},
+    i18n,
+    storage::{GuildConfig, GuildConfigContainer, StorageContainer},
+};
+
+use serenity::{
+    builder::CreateEmbed,
+    model::{
+        id::{ChannelId, GuildId, RoleId},
+        misc::Mentionable,
+    },
+};
+
+/// The names of the settings, as they're used with `config set` and `config reset`
+static SETTINGS: [(&str, &str); 16] = [
+    ("prefix", "The prefix I respond to in this server"),
+    ("playerrole", "The role given to players during a game"),
+    ("meetingroom", "The channel used as the meeting room"),
+    (
+        "announcements",
+        "The channel game announcements are sent in",
+    ),
+    ("roomscategory", "The category players' rooms are made in"),
+    (
+        "deleterooms",
+        "Whether to delete the players' rooms when a game ends",
+    ),
+    (
+        "statusboard",
+        "Whether to show a picture of the game in `gameinfo` and block announcements",
+    ),
+    (
+        "locale",
+        "The language used with players that didn't pick one with `language`",
+    ),
+    ("food", "How many portions of food players start with"),
+    ("maxnotes", "How many notes fit in a memo book"),
+    (
+        "timeout",
+        "How many minutes players have to make a decision",
+    ),
+    (
+        "reminder",
+        "How many minutes before a decision's deadline players get reminded",
+    ),
+    (
+        "murderdefault",
+        "Whom the King targets when they don't decide in time",
+    ),
+    (
+        "assistantdefault",
+        "What the Sorcerer or Knight do when they don't decide in time",
+    ),
+    (
+        "assassinationdefault",
+        "Whom the Revolutionary targets when they don't decide in time",
+    ),
+    (
+        "blockminutes",
+        "How many minutes time blocks last in games I host myself",
+    ),
+];
+
+/// The settings that are part of a game's rules, which `schedule` can change for a single game
+static RULE_SETTINGS: [&str; 7] = [
+    "food",
+    "maxnotes",
+    "timeout",
+    "reminder",
+    "murderdefault",
+    "assistantdefault",
+    "assassinationdefault",
+];
+
+#[command]
+#[only_in(guilds)]
+#[required_permissions("MANAGE_GUILD")]
+#[description("Shows the settings used for games in this server")]
+#[sub_commands(config_set, config_reset)]
+pub async fn config(ctx: &Context, msg: &Message) -> CommandResult {
+    let config = get_config(ctx, msg.guild_id.unwrap()).await;
+    let prefix = data::prefix(ctx, msg.guild_id).await;
+
+    fn or_not_set(value: Option<String>) -> String {
+        value.unwrap_or_else(|| "Not set".to_string())
+    }
+
+    let values = [
+        prefix.clone(),
+        or_not_set(config.player_role.map(|r| r.mention().to_string())),
+        or_not_set(config.meeting_room.map(|c| c.mention().to_string())),
+        or_not_set(config.announcement_channel.map(|c| c.mention().to_string())),
+        config
+            .rooms_category
+            .map(|c| c.mention().to_string())
+            .unwrap_or_else(|| config.rooms_category_name.clone()),
+        config.delete_rooms_on_end.to_string(),
+        config.status_board.to_string(),
+        config.locale.clone(),
+        config.rules.food_portions.to_string(),
+        config.rules.max_notes.to_string(),
+        config.rules.decision_timeout_minutes.to_string(),
+        config.rules.decision_reminder_minutes.to_string(),
+        config.rules.decision_defaults.murder_target.to_string(),
+        config.rules.decision_defaults.assistant.to_string(),
+        config
+            .rules
+            .decision_defaults
+            .assassination_target
+            .to_string(),
+        config.rules.block_minutes.to_string(),
+    ];
+
+    let mut embed = CreateEmbed::default();
+    embed
+        .title("Server configuration")
+        .description(format!(
+            "Change a setting with `{0}config set <setting> <value>`, or go back to its default with `{0}config reset <setting>`",
+            prefix
+        ))
+        .colour(0x7289DA);
+
+    for ((name, description), value) in SETTINGS.iter().zip(values.iter()) {
+        embed.field(
+            format!("{} ({})", description, name),
+            value.to_string(),
+            true,
+        );
+    }
+
+    msg.channel_id
+        .send_message(ctx, |m| m.set_embed(embed))
+        .await?;
+
+    Ok(())
+}
+
+#[command("set")]
+#[only_in(guilds)]
+#[required_permissions("MANAGE_GUILD")]
+#[description(
+    r#"Changes a setting for games in this server
+
+Valid settings are: prefix, playerrole, meetingroom, announcements, roomscategory, deleterooms, statusboard, locale, food, maxnotes, timeout, reminder, murderdefault, assistantdefault, assassinationdefault, blockminutes.
+
+(Usage and Sample usage do not include the prefix, but it still must be used)"#
+)]
+#[usage("<setting> <value>")]
+#[example("meetingroom #meeting-room")]
+pub async fn config_set(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let setting = args.single::<String>().unwrap_or_default().to_lowercase();
+    let guild = msg.guild_id.unwrap();
+
+    let mut config = get_config(ctx, guild).await;
+    if let Err(err) = set_setting(&mut config, &setting, &mut args) {
+        msg.reply(ctx, err).await?;
+        return Ok(());
+    }
+
+    save_config(ctx, guild, config).await?;
+    msg.reply(ctx, format!("Successfully changed `{}`", setting))
+        .await?;
+
+    Ok(())
+}
+
+#[command("reset")]
+#[only_in(guilds)]
+#[required_permissions("MANAGE_GUILD")]
+#[description(
+    r#"Changes a setting back to its default value. Use `all` to reset every setting.
+
+(Usage and Sample usage do not include the prefix, but it still must be used)"#
+)]
+#[usage("<setting>")]
+#[example("prefix")]
+pub async fn config_reset(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let setting = args.single::<String>().unwrap_or_default().to_lowercase();
+    let guild = msg.guild_id.unwrap();
+
+    let mut config = get_config(ctx, guild).await;
+    let default = get_defaults(ctx).await;
+    match setting.as_str() {
+        "all" => config = default,
+        "prefix" => config.prefix = default.prefix,
+        "playerrole" => config.player_role = default.player_role,
+        "meetingroom" => config.meeting_room = default.meeting_room,
+        "announcements" => config.announcement_channel = default.announcement_channel,
+        "roomscategory" => {
+            config.rooms_category = default.rooms_category;
+            config.rooms_category_name = default.rooms_category_name;
+        }
+        "deleterooms" => config.delete_rooms_on_end = default.delete_rooms_on_end,
+        "statusboard" => config.status_board = default.status_board,
+        "locale" => config.locale = default.locale,
+        "food" => config.rules.food_portions = default.rules.food_portions,
+        "maxnotes" => config.rules.max_notes = default.rules.max_notes,
+        "timeout" => config.rules.decision_timeout_minutes = default.rules.decision_timeout_minutes,
+        "reminder" => {
+            config.rules.decision_reminder_minutes = default.rules.decision_reminder_minutes
+        }
+        "murderdefault" => {
+            config.rules.decision_defaults.murder_target =
+                default.rules.decision_defaults.murder_target
+        }
+        "assistantdefault" => {
+            config.rules.decision_defaults.assistant = default.rules.decision_defaults.assistant
+        }
+        "assassinationdefault" => {
+            config.rules.decision_defaults.assassination_target =
+                default.rules.decision_defaults.assassination_target
+        }
+        "blockminutes" => config.rules.block_minutes = default.rules.block_minutes,
+        _ => {
+            msg.reply(ctx, format!("There's no setting called `{}`", setting))
+                .await?;
+            return Ok(());
+        }
+    }
+
+    save_config(ctx, guild, config).await?;
+    msg.reply(ctx, format!("Successfully reset `{}`", setting))
+        .await?;
+
+    Ok(())
+}
+
+/// Changes one of the rules in `config` to `value`, refusing settings that aren't about the rules
+pub fn set_rule(config: &mut GuildConfig, setting: &str, value: &str) -> Result<(), String> {
+    if !RULE_SETTINGS.contains(&setting) {
+        return Err(format!(
+            "`{}` isn't a rule, the rules are: {}",
+            setting,
+            RULE_SETTINGS.join(", ")
+        ));
+    }
+
+    set_setting(config, setting, &mut Args::new(value, &[]))
+}
+
+fn set_setting(config: &mut GuildConfig, setting: &str, args: &mut Args) -> Result<(), String> {
+    match setting {
+        "prefix" => {
+            let prefix = args
+                .single::<String>()
+                .map_err(|_| "You need to give me a prefix")?;
+            config.prefix = Some(prefix);
+        }
+        "playerrole" => {
+            let role = args
+                .single::<RoleId>()
+                .map_err(|_| "I couldn't get a role from your message")?;
+            config.player_role = Some(role);
+        }
+        "meetingroom" => {
+            let channel = args
+                .single::<ChannelId>()
+                .map_err(|_| "I couldn't get a channel from your message")?;
+            config.meeting_room = Some(channel);
+        }
+        "announcements" => {
+            let channel = args
+                .single::<ChannelId>()
+                .map_err(|_| "I couldn't get a channel from your message")?;
+            config.announcement_channel = Some(channel);
+        }
+        "roomscategory" => {
+            // Either an existing category to reuse, or a name for the ones made for every game
+            if let Ok(category) = args.single::<ChannelId>() {
+                config.rooms_category = Some(category);
+            } else {
+                let name = args.rest().trim();
+                if name.is_empty() {
+                    return Err("You need to give me a category or a name for one".into());
+                }
+                config.rooms_category = None;
+                config.rooms_category_name = name.to_string();
+            }
+        }
+        "deleterooms" => {
+            config.delete_rooms_on_end = args
+                .single::<bool>()
+                .map_err(|_| "You need to write either `true` or `false`")?;
+        }
+        "statusboard" => {
+            config.status_board = args
+                .single::<bool>()
+                .map_err(|_| "You need to write either `true` or `false`")?;
+        }
+        "locale" => {
+            let locale = args.single::<String>().unwrap_or_default().to_lowercase();
+            if !i18n::is_supported(&locale) {
+                return Err(format!(
+                    "The locale needs to be one of: {}",
+                    i18n::LOCALES.join(", ")
+                ));
+            }
+            config.locale = locale;
+        }
+        "food" => {
+            config.rules.food_portions = args
+                .single::<u8>()
+                .map_err(|_| "You need to give me a number between 0 and 255")?;
+        }
+        "maxnotes" => {
+            let max_notes = args
+                .single::<u32>()
+                .map_err(|_| "You need to give me a positive number")?;
+            if max_notes == 0 {
+                return Err("Memo books need room for at least one note".into());
+            }
+            config.rules.max_notes = max_notes;
+        }
+        "timeout" => {
+            let minutes = args
+                .single::<u64>()
+                .map_err(|_| "You need to give me a number of minutes")?;
+            if minutes == 0 {
+                return Err("Players need at least a minute to decide".into());
+            }
+            config.rules.decision_timeout_minutes = minutes;
+        }
+        "reminder" => {
+            config.rules.decision_reminder_minutes = args
+                .single::<u64>()
+                .map_err(|_| "You need to give me a number of minutes, 0 turns reminders off")?;
+        }
+        "murderdefault" => {
+            config.rules.decision_defaults.murder_target = args
+                .single::<String>()
+                .unwrap_or_default()
+                .parse::<TargetDefault>()?;
+        }
+        "assistantdefault" => {
+            config.rules.decision_defaults.assistant = args
+                .single::<String>()
+                .unwrap_or_default()
+                .parse::<AssistantDefault>()?;
+        }
+        "assassinationdefault" => {
+            config.rules.decision_defaults.assassination_target = args
+                .single::<String>()
+                .unwrap_or_default()
+                .parse::<TargetDefault>()?;
+        }
+        "blockminutes" => {
+            let minutes = args
+                .single::<u64>()
+                .map_err(|_| "You need to give me a number of minutes")?;
+            if minutes == 0 {
+                return Err("Time blocks need to last at least a minute".into());
+            }
+            config.rules.block_minutes = minutes;
+        }
+        _ => return Err(format!("There's no setting called `{}`", setting)),
+    }
+
+    Ok(())
+}
+
+async fn get_config(ctx: &Context, guild: GuildId) -> GuildConfig {
+    ctx.data
+        .read()
+        .await
+        .get::<GuildConfigContainer>()
+        .expect("ctx.data should always have a GuildConfigContainer")
+        .read()
+        .await
+        .get(guild)
+}
+
+async fn get_defaults(ctx: &Context) -> GuildConfig {
+    ctx.data
+        .read()
+        .await
+        .get::<GuildConfigContainer>()
+        .expect("ctx.data should always have a GuildConfigContainer")
+        .read()
+        .await
+        .defaults()
+        .clone()
+}
+
+async fn save_config(ctx: &Context, guild: GuildId, config: GuildConfig) -> CommandResult {
+    let data = ctx.data.read().await;
+    let storage = data
+        .get::<StorageContainer>()
+        .expect("ctx.data should always have a StorageContainer");
+
+    data.get::<GuildConfigContainer>()
+        .expect("ctx.data should always have a GuildConfigContainer")
+        .write()
+        .await
+        .update(storage, guild, |c| *c = config)?;
+
+    Ok(())
+}
@@ -102,7 +102,7 @@ Are you sure you want to do this? React with 🇾 if so, and with 🇳 if not.
         .author_id(msg.author.id)
         .await
     {
-        info!("We got woke up");
+        info!(user = %msg.author.id, "Got an answer about deleting the category");
         if reaction.as_inner_ref().emoji.unicode_eq(REACTIONS[0]) {
             for channel in channels_to_delete {
                 channel.delete(ctx).await?;
@@ -1,6 +1,5 @@
-use std::collections::HashMap;
-
 use super::prelude::*;
+use crate::i18n;
 
 use serenity::framework::standard::{macros::check, CommandOptions, Reason};
 
@@ -11,11 +10,12 @@ pub static BROKEN_GAME_CHECK_CONTRACT: &str = r#"This command either misses a `#
 #[name = "StandardGameCheck"]
 pub async fn standard_game(
     ctx: &Context,
-    _: &Message,
+    msg: &Message,
     _: &mut Args,
     command: &CommandOptions,
 ) -> Result<(), Reason> {
     let data = ctx.data.read().await;
+    let locale = i18n::locale_from(&data, msg.guild_id, Some(msg.author.id)).await;
     let game = data.get::<GameContainer>();
     if let Some(game) = game {
         let game = game.read().await;
@@ -23,22 +23,25 @@ pub async fn standard_game(
         if !game.is_started() {
             return Err(make_reason(
                 command,
+                &locale,
                 "Game wasn't started",
-                &*error_messages::GAME_NOT_STARTED,
+                error_messages::GAME_NOT_STARTED,
             ));
         }
         if game.is_ended() {
             return Err(make_reason(
                 command,
+                &locale,
                 "Game has ended",
-                &*error_messages::GAME_ENDED,
+                error_messages::GAME_ENDED,
             ));
         }
     } else {
         return Err(make_reason(
             command,
+            &locale,
             "No game exists",
-            &*error_messages::NEEDS_GAME_TO_EXIST,
+            error_messages::NEEDS_GAME_TO_EXIST,
         ));
     }
 
@@ -49,11 +52,12 @@ pub async fn standard_game(
 #[name("GameCheckAllowGameEnded")]
 pub async fn game_check_allow_game_ended(
     ctx: &Context,
-    _: &Message,
+    msg: &Message,
     _: &mut Args,
     command: &CommandOptions,
 ) -> Result<(), Reason> {
     let data = ctx.data.read().await;
+    let locale = i18n::locale_from(&data, msg.guild_id, Some(msg.author.id)).await;
     let game = data.get::<GameContainer>();
     if let Some(game) = game {
         let game = game.read().await;
@@ -61,15 +65,17 @@ pub async fn game_check_allow_game_ended(
         if !game.is_started() {
             return Err(make_reason(
                 command,
+                &locale,
                 "Game wasn't started",
-                &*error_messages::GAME_NOT_STARTED,
+                error_messages::GAME_NOT_STARTED,
             ));
         }
     } else {
         return Err(make_reason(
             command,
+            &locale,
             "No game exists",
-            &*error_messages::NEEDS_GAME_TO_EXIST,
+            error_messages::NEEDS_GAME_TO_EXIST,
         ));
     }
     Ok(())
@@ -94,27 +100,30 @@ pub async fn user_is_playing(
 
     let user = msg.author.id;
     let player = game.player(user);
+    let locale = i18n::locale_from(&data, msg.guild_id, Some(user)).await;
 
     if player.is_none() {
         return Err(make_reason(
             command,
+            &locale,
             "User is not a player",
-            &*error_messages::USER_NOT_A_PLAYER,
+            error_messages::USER_NOT_A_PLAYER,
         ));
     }
 
     Ok(())
 }
 
-fn make_reason(command: &CommandOptions, log: &str, map: &HashMap<&str, &str>) -> Reason {
+fn make_reason(command: &CommandOptions, locale: &str, log: &str, messages: &str) -> Reason {
     use Reason::{Log, UserAndLog};
-    match map.get(command.names[0]) {
-        Some(message) => UserAndLog {
-            user: message.to_string(),
+    let key = format!("{}.{}", messages, command.names[0]);
+    match i18n::lookup(i18n::FALLBACK_LOCALE, &key) {
+        Some(_) => UserAndLog {
+            user: i18n::tr(locale, &key),
             log: log.to_string(),
         },
         None => Log(format!(
-            "\n{ascii}\n\tMissing entry in hashmaps for {cmd}",
+            "\n{ascii}\n\tMissing entry in the catalogue for {cmd}",
             ascii = error_messages::CHECK_BAD,
             cmd = command.names[0]
         )),
@@ -123,185 +132,19 @@ fn make_reason(command: &CommandOptions, log: &str, map: &HashMap<&str, &str>) -
 
 /// Module containing statics for different error messages
 mod error_messages {
-    use once_cell::sync::Lazy;
-    use std::collections::HashMap;
-
-    /// Error messages for commands that need a game to exist in `ctx.data`
-    pub static NEEDS_GAME_TO_EXIST: Lazy<HashMap<&str, &str>> = Lazy::new(|| {
-        let mut map = HashMap::new();
-
-        map.insert(
-            "endgathering",
-            "You can't end a gathering if there's no game running!",
-        );
-        map.insert("give", "You can't give items when there's no game running!");
-        map.insert(
-            "inventory",
-            "You can't look into your bag when there's no game running",
-        );
-        map.insert(
-            "nextblock",
-            "You can't go to the next time block if there's no game running!",
-        );
-        map.insert(
-            "notes",
-            "You can't take a look into your memo book when there isn't a game running on!",
-        );
-        map.insert(
-            "stab",
-            "You can't stab someone when there isn't a game running!",
-        );
-        map.insert(
-            "startgathering",
-            "You can't start a gathering if there's no game running!",
-        );
-        map.insert(
-            "substitute",
-            "You can't 「 substitute 」 with someone when you're not in a game!",
-        );
-        map.insert(
-            "writenote",
-            "You can't write a note to your memo book when a game hasn't started yet",
-        );
-        map.insert(
-            "shownote",
-            "You can't show a note from your memo book when there isn't a game running!",
-        );
-        map.insert(
-            "ripnote",
-            "You can't rip a note out of your memo book when there's no game running",
-        );
-        map.insert(
-            "showlogs",
-            "You can't see secret meeting logs when there's no game running!",
-        );
-
-        map
-    });
-
-    /// Error messages that need `game.state()` to be different than `GameState::NotStarted`
-    pub static GAME_NOT_STARTED: Lazy<HashMap<&str, &str>> = Lazy::new(|| {
-        let mut map = HashMap::new();
-
-        map.insert(
-            "endgathering",
-            "You can't end a meeting in the big room if the game hasn't started yet!",
-        );
-        map.insert("give", "You can't give items when there's no game running!");
-        map.insert(
-            "inventory",
-            "You can't look into your bag when there's no game running",
-        );
-        map.insert(
-            "nextblock",
-            "You can't go to the next time block if there's no game running!",
-        );
-        map.insert(
-            "notes",
-            "You can't take a look into your memo book when there isn't a game running on!",
-        );
-        map.insert(
-            "stab",
-            "You can't stab someone when there isn't a game running!",
-        );
-        map.insert(
-            "startgathering",
-            "You can't start a gathering if there's no game running!",
-        );
-        map.insert(
-            "substitute",
-            "You can't 「 substitute 」 with someone when you're not in a game!",
-        );
-        map.insert(
-            "writenote",
-            "You can't write a note to your memo book before the game starts",
-        );
-        map.insert(
-            "shownote",
-            "You can't show a note from your memo book before the game starts",
-        );
-        map.insert(
-            "ripnote",
-            "You can't rip a note out of your memo book when the game hasn't started yet",
-        );
-        map.insert(
-            "showlogs",
-            "You can't see secret meeting logs before a game started!",
-        );
-
-        map
-    });
-
-    /// Error messages for commands that need `game.state()` to be different than `GameState::GameEnded`
-    pub static GAME_ENDED: Lazy<HashMap<&str, &str>> = Lazy::new(|| {
-        let mut map = HashMap::new();
-
-        map.insert(
-            "endgathering",
-            "You can't end a meeting in the big room if the game has ended!",
-        );
-        map.insert("give", "You can't give items after a game has ended!");
-        map.insert(
-            "nextblock",
-            "You can't go to the next time block if the game has ended.",
-        );
-        map.insert("stab", "You can't stab someone after a game has ended!");
-        map.insert(
-            "startgathering",
-            "You can't start a meeting in the big room after the game has ended!",
-        );
-        map.insert(
-            "substitute",
-            "You can't 「 substitute 」 with someone after the game has ended!",
-        );
-        map.insert(
-            "writenote",
-            "You can't write a note to your memo book after a game has ended",
-        );
-        map.insert(
-            "ripnote",
-            "You can't rip a note out of your memo book after the game has ended!",
-        );
+    //! The messages themselves are in the catalogue, under `<prefix>.<command name>`
 
-        map
-    });
+    /// Prefix of the error messages for commands that need a game to exist in `ctx.data`
+    pub static NEEDS_GAME_TO_EXIST: &str = "checks.no_game";
 
-    pub static USER_NOT_A_PLAYER: Lazy<HashMap<&str, &str>> = Lazy::new(|| {
-        let mut map = HashMap::new();
+    /// Prefix of the error messages for commands that need `game.state()` to be different than `GameState::NotStarted`
+    pub static GAME_NOT_STARTED: &str = "checks.not_started";
 
-        map.insert(
-            "inspect",
-            "You can't look at objects when you're not in a game.",
-        );
-        map.insert(
-            "lookaround",
-            "You can't look around yourself when you're not in a game.",
-        );
-        map.insert("give", "You can't give items when you're not in a game");
-        map.insert(
-            "inventory",
-            "You can't look into your bag when you aren't in the game",
-        );
-        map.insert(
-            "notes",
-            "You can't take a look at your note when you're not part of the game",
-        );
-        map.insert(
-            "showlogs",
-            "You can't show secret meeting logs when you're not in a game!",
-        );
-        map.insert(
-            "stab",
-            "You can't stab someone when you're not in the game!",
-        );
-        map.insert(
-            "substitute",
-            "You can't 「 substitute 」  with someone when you aren't in a game!",
-        );
-        map.insert("give", "You can't give items when you're not in a game");
+    /// Prefix of the error messages for commands that need `game.state()` to be different than `GameState::GameEnded`
+    pub static GAME_ENDED: &str = "checks.ended";
 
-        map
-    });
+    /// Prefix of the error messages for commands that need the user to be playing
+    pub static USER_NOT_A_PLAYER: &str = "checks.not_a_player";
 
     pub static CHECK_BAD: &str = r#"
 
@@ -0,0 +1,57 @@
+use super::prelude::*;
+use crate::{
+    helpers::preflight::{self, GameSetup},
+    i18n,
+    storage::GuildConfigContainer,
+};
+
+#[command]
+#[only_in(guilds)]
+#[description(
+    "Checks whether a game could be started, and lists everything that would stop it from starting"
+)]
+pub async fn doctor(ctx: &Context, msg: &Message) -> CommandResult {
+    let guild = msg.guild_id.unwrap();
+
+    let game = ctx.data.read().await.get::<GameContainer>().cloned();
+    let setup = match game {
+        Some(game) => GameSetup::from_game(&*game.read().await),
+        None => {
+            let config = ctx
+                .data
+                .read()
+                .await
+                .get::<GuildConfigContainer>()
+                .expect("ctx.data should always have a GuildConfigContainer")
+                .read()
+                .await
+                .get(guild);
+            GameSetup::from_config(guild, &config)
+        }
+    };
+
+    let locale = i18n::locale(ctx, Some(guild), Some(msg.author.id)).await;
+    let problems = preflight::check(ctx, &setup, &locale).await?;
+
+    msg.channel_id
+        .send_message(ctx, |m| {
+            m.embed(|e| {
+                if problems.is_empty() {
+                    e.title(i18n::tr(&locale, "doctor.ok_title"))
+                        .description(i18n::tr(&locale, "doctor.ok_description"))
+                        .colour(0x0dd910) // Green
+                } else {
+                    e.title(i18n::tr_with(
+                        &locale,
+                        "doctor.problems_title",
+                        &[("count", &problems.len())],
+                    ))
+                    .description(preflight::describe(&problems))
+                    .colour(0xbf2419) // Red
+                }
+            })
+        })
+        .await?;
+
+    Ok(())
+}
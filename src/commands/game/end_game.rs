@@ -1,44 +1,73 @@
-use super::prelude::*;
+use super::{prelude::*, running_game};
+use crate::{
+    lobby,
+    storage::{self, StorageContainer},
+};
 
-use typemap_rev::Entry;
+use tracing::warn;
 
 #[command("endgame")]
 #[only_in(guilds)]
 #[description("Forcefully end a game")]
 pub async fn end_game(ctx: &Context, msg: &Message) -> CommandResult {
-    end_game_impl(ctx, msg, false).await
+    if !finish_game(ctx).await? {
+        msg.reply(ctx, "You can't end a game if there isn't one running")
+            .await?;
+    }
+
+    Ok(())
 }
 
-pub async fn end_game_impl(ctx: &Context, msg: &Message, in_shutdown: bool) -> CommandResult {
-    let mut data = ctx.data.write().await;
+/// Ends the running game and cleans up after it, returning whether there was one
+///
+/// The game stops being the running game first, so that `ctx.data` isn't held while waiting on the
+/// game, which may itself be waiting on `ctx.data`, e.g. while moving on to the next time block. If
+/// cleaning up fails, the game is put back so it can be tried again.
+pub async fn finish_game(ctx: &Context) -> CommandResult<bool> {
+    let (game, storage) = {
+        let mut data = ctx.data.write().await;
+        let game = match data.remove::<GameContainer>() {
+            Some(game) => game,
+            None => return Ok(false),
+        };
+        let storage = data
+            .get::<StorageContainer>()
+            .cloned()
+            .expect("ctx.data should always have a StorageContainer");
+        (game, storage)
+    };
 
-    {
-        let game = data.get::<GameContainer>();
-        match game {
-            Some(game) => {
-                let mut game = game.write().await;
-                game.end(ctx).await?;
+    let ended = {
+        let mut game = game.write().await;
+        let ended = game.end(ctx).await;
+        if ended.is_ok() {
+            if !game.is_started() {
+                lobby::close(ctx, &game).await;
             }
-            None => {
-                if !in_shutdown {
-                    msg.reply(ctx, "You can't end a game if there isn't one running")
-                        .await?;
-                }
-                return Ok(());
+            if let Err(err) = storage::save_game(&storage, &game, true) {
+                warn!("Couldn't save the game as finished: {}", err);
             }
         }
-    }
+        ended
+    };
 
-    let game_entry = data.entry::<GameContainer>();
-    match game_entry {
-        Entry::Occupied(game_container) => {
-            game_container.remove();
-        }
-        Entry::Vacant(_) => {
-            // This can happen when in_shutdown == true
-            return Ok(());
+    if let Err(err) = ended {
+        let mut data = ctx.data.write().await;
+        if data.get::<GameContainer>().is_none() {
+            data.insert::<GameContainer>(game);
         }
+        return Err(err);
     }
 
-    Ok(())
+    Ok(true)
+}
+
+/// Stops running the game with the ID `id` without cleaning up after it
+///
+/// It's still kept on disk as unfinished, so the host gets asked what to do with it once the bot
+/// is restarted.
+pub async fn abandon_game(ctx: &Context, id: u64) {
+    if running_game(ctx, id).await.is_some() {
+        ctx.data.write().await.remove::<GameContainer>();
+    }
 }
@@ -17,6 +17,15 @@ pub async fn end_gathering(ctx: &Context, msg: &Message) -> CommandResult {
         return Ok(());
     }
 
+    if game.is_paused() {
+        msg.reply(
+            ctx,
+            "The game is paused until you decide what happens to the players that are gone.",
+        )
+        .await?;
+        return Ok(());
+    }
+
     if !game.can_end_gathering() {
         msg.reply(
             ctx,
@@ -1,4 +1,5 @@
 use super::prelude::*;
+use crate::lobby;
 
 use serenity::model::id::UserId;
 use tracing::info;
@@ -9,16 +10,14 @@ use tracing::info;
 pub async fn forceadd(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
     info!("{:?}", msg);
 
-    let data = ctx.data.write().await;
-    let game = {
-        let game = data.get::<GameContainer>().unwrap();
-
-        &mut game.write().await
-    };
+    // Refreshing the lobby message reads ctx.data, so it mustn't be kept locked
+    let game = get_game_guard(ctx).await?;
+    let mut game = game.write().await;
 
     for user in args.iter::<UserId>() {
         game.join(user?)?;
     }
+    lobby::refresh(ctx, &game).await;
 
     Ok(())
 }
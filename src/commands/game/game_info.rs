@@ -1,6 +1,8 @@
 use super::prelude::*;
+use crate::helpers::status_board;
 
 use serenity::model::misc::Mentionable;
+use tracing::warn;
 
 #[command("gameinfo")]
 #[only_in(guilds)]
@@ -76,8 +78,20 @@ pub async fn game_info(ctx: &Context, msg: &Message) -> CommandResult {
         ),
     ];
 
+    let board = if game.status_board_enabled() {
+        status_board::build(ctx, &game).await.unwrap_or_else(|err| {
+            warn!("Couldn't render the status board: {}", err);
+            None
+        })
+    } else {
+        None
+    };
+
     msg.channel_id
         .send_message(ctx, |m| {
+            if let Some(board) = &board {
+                m.add_file(board.attachment());
+            }
             m.embed(|e| {
                 e.author(|a| {
                     if !game.is_started() {
@@ -121,6 +135,10 @@ pub async fn game_info(ctx: &Context, msg: &Message) -> CommandResult {
                     })
                 });
 
+                if board.is_some() {
+                    e.image(format!("attachment://{}", status_board::FILE_NAME));
+                }
+
                 e
             })
         })
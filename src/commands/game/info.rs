@@ -1,4 +1,4 @@
-use crate::data::Prefix;
+use crate::data;
 
 use super::prelude::*;
 
@@ -44,7 +44,7 @@ You win if your classe's win condition has been fulfilled.
                 .field("How to play", r#"
 You can join a game using `!join`, and leave it using `!leave`.
 
-Depending on the role you get, you might have to choose whether to kill a player or not, this is done by reacting to the bot's message. (You should understand what I mean when playing).
+Depending on the role you get, you might have to choose whether to kill a player or not, this is done by reacting to the bot's message. (You should understand what I mean when playing). Choices have a deadline, and if you miss it the server's default is picked for you.
 
 There are many commands you can use `!help` to discover them all.
                 "#, false)
@@ -59,8 +59,7 @@ There are many commands you can use `!help` to discover them all.
 #[aliases("intro")]
 #[description("Gives the user a short intro to what is happening")]
 pub async fn info(ctx: &Context, msg: &Message) -> CommandResult {
-    let data = ctx.data.read().await;
-    let prefix = data.get::<Prefix>().unwrap();
+    let prefix = data::prefix(ctx, msg.guild_id).await;
 
     msg
         .channel_id
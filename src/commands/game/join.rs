@@ -1,30 +1,28 @@
 use super::prelude::*;
+use crate::{
+    i18n,
+    lobby::{self, SignUp},
+};
+
 use tracing::info;
 
 #[command]
 #[only_in(guilds)]
 #[bucket = "join_leave_ratelimit_bucket"]
-#[description("Allows you to join a game that has yet to start and that has less than 6 players")]
+#[description(
+    "Allows you to join a game that has yet to start, or its waitlist if it already has 6 players"
+)]
 pub async fn join(ctx: &Context, msg: &Message) -> CommandResult {
-    let mut data = ctx.data.write().await;
-    let game = data.get_mut::<GameContainer>();
+    // Games the bot hosts start on their own once they're full, which needs ctx.data, so it
+    // mustn't be kept locked while waiting on the game
+    let game = ctx.data.read().await.get::<GameContainer>().cloned();
+    let locale = i18n::locale(ctx, msg.guild_id, Some(msg.author.id)).await;
 
     if game.is_some() {
         let mut game = game.unwrap().write().await;
 
-        let member = msg.member(ctx).await?;
-        let mut member_may_have_admin_perms = member.permissions(ctx).await?.administrator();
-        member_may_have_admin_perms |= msg.guild(ctx).await.unwrap().owner_id == msg.author.id;
-
-        if member_may_have_admin_perms {
-            msg.reply(
-                ctx,
-                "You can't join a game if you're the Owner of a server or an administrator!",
-            )
-            .await?;
-        } else {
-            let result = game.join(msg.author.id);
-            if result.is_ok() {
+        match lobby::sign_up(ctx, &mut game, msg.author.id).await? {
+            SignUp::Joined => {
                 info!("Successfully added a new user to the game");
                 msg.reply(
                     ctx,
@@ -34,11 +32,24 @@ pub async fn join(ctx: &Context, msg: &Message) -> CommandResult {
                     ),
                 )
                 .await?;
-            } else {
-                info!("Couldn't add new user, error is {:?}", result.unwrap_err());
-                msg.reply(ctx, format!("{}", result.unwrap_err())).await?;
+            }
+            SignUp::Waitlisted(position) => {
+                info!("Added a new user to the waitlist");
+                msg.reply(
+                    ctx,
+                    format!(
+                        "The game is full, so you're number {} on its waitlist. You'll get in if someone leaves.",
+                        position
+                    ),
+                )
+                .await?;
+            }
+            SignUp::Refused(reason) => {
+                info!("Couldn't add new user: {}", reason);
+                msg.reply(ctx, i18n::tr(&locale, reason)).await?;
             }
         }
+        lobby::refresh(ctx, &game).await;
     } else {
         info!("User tried joining inexistent user");
         msg.reply(ctx, "you can't join a game if there aren't any in progress")
@@ -1,4 +1,5 @@
 use super::prelude::*;
+use crate::{i18n, lobby};
 
 use tracing::info;
 
@@ -7,8 +8,9 @@ use tracing::info;
 #[bucket = "join_leave_ratelimit_bucket"]
 #[description("Allows you to leave a game")]
 pub async fn leave(ctx: &Context, msg: &Message) -> CommandResult {
-    let mut data = ctx.data.write().await;
-    let game = data.get_mut::<GameContainer>();
+    // See `join` for why ctx.data isn't kept locked
+    let game = ctx.data.read().await.get::<GameContainer>().cloned();
+    let locale = i18n::locale(ctx, msg.guild_id, Some(msg.author.id)).await;
 
     if game.is_some() {
         let mut game = game.unwrap().write().await;
@@ -18,14 +20,18 @@ pub async fn leave(ctx: &Context, msg: &Message) -> CommandResult {
             info!("User tried leaving running game");
             return Ok(());
         } else {
-            let result = game.leave(msg.author.id);
-            if result.is_ok() {
-                info!("A user successfully left a game");
-                msg.reply(ctx, "You've successfully left the game :c")
-                    .await?;
-            } else {
-                info!("User couldn't leave, error is {:?}", result.unwrap_err());
-                msg.reply(ctx, format!("{}", result.unwrap_err())).await?;
+            match lobby::drop_out(&mut game, msg.author.id) {
+                Ok(admitted) => {
+                    info!("A user successfully left a game");
+                    msg.reply(ctx, "You've successfully left the game :c")
+                        .await?;
+                    lobby::announce_admitted(ctx, &game, msg.channel_id, &admitted).await;
+                    lobby::refresh(ctx, &game).await;
+                }
+                Err(err) => {
+                    info!("User couldn't leave, error is {:?}", err);
+                    msg.reply(ctx, i18n::tr(&locale, err.key())).await?;
+                }
             }
         }
     } else {
@@ -0,0 +1,83 @@
+use super::prelude::*;
+use crate::{helpers::meeting_graph::MeetingGraph, metrics};
+
+use serenity::{http::AttachmentType, model::misc::Mentionable};
+use std::{borrow::Cow, collections::HashMap};
+use tracing::warn;
+
+#[command("meetinggraph")]
+#[description(
+    r#"
+Shows who met whom in secret over the course of the game, once it's over.
+
+The graph is attached both as a picture and as a Graphviz DOT file, with an arrow going from each player to the one they asked to meet."#
+)]
+#[checks(GameCheckAllowGameEnded)]
+#[only_in(guilds)]
+pub async fn meeting_graph(ctx: &Context, msg: &Message) -> CommandResult {
+    let game_guard = get_game_guard(ctx).await?;
+    let game = game_guard.read().await;
+
+    if !game.is_ended() {
+        msg.reply(
+            ctx,
+            "No peeking! Who met whom stays a secret until the game is over",
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let players = game
+        .players()
+        .expect("meeting_graph: GameCheckAllowGameEnded broke its contract");
+
+    let mut names = HashMap::new();
+    for &id in players.keys() {
+        match id.to_user(ctx).await {
+            Ok(user) => {
+                names.insert(id, user.tag());
+            }
+            Err(err) => warn!("Couldn't fetch the name of {}: {}", id, err),
+        }
+    }
+
+    let graph = MeetingGraph::new(players, &names);
+    if graph.is_empty() {
+        msg.reply(ctx, "Nobody met anyone in secret this game!")
+            .await?;
+        return Ok(());
+    }
+
+    let rooms = players
+        .values()
+        .filter_map(|player| {
+            player
+                .room_number()
+                .map(|room| format!("Room {}: {}", room, player.id().mention()))
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    drop(game);
+
+    let dot = graph.to_dot();
+    let png = tokio::task::spawn_blocking(move || {
+        metrics::time_render("meeting_graph", || graph.render())
+    })
+    .await??;
+
+    msg.channel_id
+        .send_message(ctx, |m| {
+            m.content(format!("Secret meetings of the last game\n{}", rooms))
+                .add_file(AttachmentType::Bytes {
+                    data: Cow::Owned(png),
+                    filename: "meetings.png".to_string(),
+                })
+                .add_file(AttachmentType::Bytes {
+                    data: Cow::Owned(dot.into_bytes()),
+                    filename: "meetings.dot".to_string(),
+                })
+        })
+        .await?;
+
+    Ok(())
+}
@@ -1,4 +1,5 @@
 // Command modules
+mod doctor;
 mod end_game;
 mod end_gathering;
 mod flavour;
@@ -9,16 +10,20 @@ mod info;
 mod inventory;
 mod join;
 mod leave;
+mod meeting_graph;
 mod new_game;
 mod next_block;
 mod notes;
+mod replace;
 mod roles;
 mod secret_meeting_log;
 mod stab;
 mod start;
 mod start_gathering;
 mod substitute;
+mod theme;
 
+pub use doctor::*;
 pub use end_game::*;
 pub use end_gathering::*;
 pub use flavour::*;
@@ -29,20 +34,27 @@ pub use info::*;
 pub use inventory::*;
 pub use join::*;
 pub use leave::*;
+pub use meeting_graph::*;
 pub use new_game::*;
 pub use next_block::*;
 pub use notes::*;
+pub use replace::*;
 pub use roles::*;
 pub use secret_meeting_log::*;
 pub use stab::*;
 pub use start::*;
 pub use start_gathering::*;
 pub use substitute::*;
+pub use theme::*;
 
 mod checks;
 
 use super::prelude::*;
 pub use crate::game::Game;
+use crate::storage::{self, StorageContainer};
+
+use serenity::model::id::GuildId;
+use tracing::{warn, Span};
 
 pub struct GameContainer;
 
@@ -50,6 +62,49 @@ impl TypeMapKey for GameContainer {
     type Value = Arc<RwLock<Game>>;
 }
 
+/// Saves a snapshot of the running game, if there's one, to disk
+pub async fn persist_game(ctx: &Context) {
+    // Whoever holds the game may be waiting on ctx.data, so it mustn't be kept locked meanwhile
+    let (game, storage) = {
+        let data = ctx.data.read().await;
+        match (data.get::<GameContainer>(), data.get::<StorageContainer>()) {
+            (Some(game), Some(storage)) => (Arc::clone(game), Arc::clone(storage)),
+            _ => return,
+        }
+    };
+
+    if let Err(err) = storage::save_game(&storage, &*game.read().await, false) {
+        warn!("Couldn't save the game: {}", err);
+    }
+}
+
+/// The running game, if it's still the one with the ID `id`
+pub async fn running_game(ctx: &Context, id: u64) -> Option<Arc<RwLock<Game>>> {
+    let game = ctx.data.read().await.get::<GameContainer>().cloned()?;
+    let is_running = game.read().await.id() == id;
+    if is_running {
+        Some(game)
+    } else {
+        None
+    }
+}
+
+/// The span of the game running in `guild`, or one that's disabled if there's none
+///
+/// This is looked up for every message, so it doesn't wait on a game that's busy, e.g. moving on
+/// to the next time block, and gives a disabled span instead.
+pub async fn game_span(ctx: &Context, guild: Option<GuildId>) -> Span {
+    let game = ctx.data.read().await.get::<GameContainer>().cloned();
+    let game = match &game {
+        Some(game) => game.try_read(),
+        None => return Span::none(),
+    };
+    match game {
+        Ok(game) if Some(game.guild()) == guild => game.span(),
+        _ => Span::none(),
+    }
+}
+
 mod prelude {
     pub use super::{checks::*, GameContainer};
     pub use crate::{commands::prelude::*, game::Game};
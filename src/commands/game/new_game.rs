@@ -1,59 +1,281 @@
 use super::prelude::*;
-use crate::data::Prefix;
+use crate::{
+    autopilot, commands::config::set_rule, config::FeaturesContainer, data::prefix_from,
+    helpers::start_time, i18n, lobby, storage::GuildConfigContainer,
+};
 
+use chrono::{DateTime, Duration, Utc};
 use serenity::model::id::{ChannelId, RoleId};
 
+/// How a new game gets going
+enum Kind {
+    /// The member that made it hosts it
+    Hosted,
+    /// The bot hosts it, starting it after this many minutes, or once six players joined
+    Automated { starts_in: Option<u32> },
+    /// The member that made it hosts it, but it starts at the given time, with some of the rules
+    /// changed from the server's configuration
+    Scheduled {
+        starts_at: DateTime<Utc>,
+        rule_changes: Vec<(String, String)>,
+    },
+}
+
 #[command("newgame")]
 #[only_in(guilds)]
-#[description("Creates a new game")]
-pub async fn new_game(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
-    // So, in order to avoid a game being created during our argument parsing[0], we
-    // hold a writer lock during our argument parsing to avoid such a race condition.
-    //
-    // [0] We want to avoid a game being created during argument parsing, so the earliest
-    //     person wanting to make a new game, will actually be the one creating a game.
-    let mut data = ctx.data.write().await;
+#[description(
+    r#"Creates a new game
 
-    let meeting_room = args.single::<ChannelId>();
-    let meeting_room_id = meeting_room.unwrap_or(msg.channel_id);
+Any of the arguments you don't pass in are taken from the server's configuration (see `config`)."#
+)]
+#[usage("[meeting room] [player role] [announcement channel] [delete rooms on game end]")]
+pub async fn new_game(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    create_game(ctx, msg, args, Kind::Hosted).await
+}
 
-    let player_role = args.single::<RoleId>();
-    let player_role_id = player_role.unwrap_or(
-        msg.guild(ctx)
-            .await
-            .unwrap()
-            .role_by_name("The Guests")
-            .unwrap()
-            .id,
-    );
+#[command("autogame")]
+#[only_in(guilds)]
+#[description(
+    r#"Creates a new game that I host myself, so that everyone can play in it
 
-    let announcement_channel = args.single::<ChannelId>();
-    let announcement_channel_id = announcement_channel.unwrap_or(msg.channel_id);
+I start it as soon as 6 players joined, or after the given number of minutes if there's enough of them by then. Time blocks last as long as the `blockminutes` setting says, and I clean up once the game is over. The rest of the arguments work like they do for `newgame`."#
+)]
+#[usage("[minutes until it starts] [meeting room] [player role] [announcement channel] [delete rooms on game end]")]
+#[example("15")]
+pub async fn auto_game(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let starts_in = args.single::<u32>().ok();
+    create_game(ctx, msg, args, Kind::Automated { starts_in }).await
+}
+
+#[command]
+#[only_in(guilds)]
+#[description(
+    r#"Creates a game that starts at the given time, which players can sign up for until then
 
-    let delete_rooms_category_on_game_end = args.single::<bool>().unwrap_or(true);
+The time is either how long from now the game starts, like `90m` or `1h30m`, or a date and time, like `2021-06-05 16:00` in UTC or `2021-06-05T18:00+02:00`. The game's rules can be changed from the server's configuration with `setting=value` pairs, using the names `config` has for them: food, maxnotes, timeout, reminder, murderdefault, assistantdefault and assassinationdefault.
 
-    if data.get::<GameContainer>().is_some() {
-        msg.reply(ctx, "You cannot start a game if one is already running")
+Players get pinged 10 minutes before the game starts. If 6 of them signed up by then it starts on its own, otherwise it's up to you to start it."#
+)]
+#[usage("<time> [setting=value...]")]
+#[example("2h food=5 timeout=15")]
+pub async fn schedule(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let now = Utc::now();
+    let time = args.single::<String>().unwrap_or_default();
+    let mut starts_at = start_time::parse(&time, now);
+    if starts_at.is_none() {
+        // The date and the time of day may be written apart
+        let date_and_time = args
+            .current()
+            .and_then(|time_of_day| start_time::parse(&format!("{} {}", time, time_of_day), now));
+        if date_and_time.is_some() {
+            args.advance();
+            starts_at = date_and_time;
+        }
+    }
+
+    let starts_at = match starts_at {
+        Some(starts_at) if starts_at > now => starts_at,
+        Some(_) => {
+            msg.reply(
+                ctx,
+                "You can't schedule a game for a time that already passed",
+            )
             .await?;
-    } else {
-        data.insert::<GameContainer>(Arc::new(RwLock::new(Game::new(
-            msg.guild_id.unwrap(),
-            msg.author.id,
-            meeting_room_id,
-            announcement_channel_id,
-            player_role_id,
-            delete_rooms_category_on_game_end,
-        ))));
-        msg.channel_id
-            .say(
+            return Ok(());
+        }
+        None => {
+            msg.reply(
+                ctx,
+                "I couldn't tell when the game should start. Write it like `90m`, `2021-06-05 16:00` (in UTC) or `2021-06-05T18:00+02:00`",
+            )
+            .await?;
+            return Ok(());
+        }
+    };
+
+    let mut rule_changes = vec![];
+    for change in args.iter::<String>() {
+        let change = change.unwrap_or_default();
+        let mut parts = change.splitn(2, '=');
+        match (parts.next(), parts.next()) {
+            (Some(setting), Some(value)) => {
+                rule_changes.push((setting.to_lowercase(), value.to_string()))
+            }
+            _ => {
+                msg.reply(
+                    ctx,
+                    format!(
+                        "Rules are changed with `setting=value`, which `{}` isn't",
+                        change
+                    ),
+                )
+                .await?;
+                return Ok(());
+            }
+        }
+    }
+
+    create_game(
+        ctx,
+        msg,
+        Args::new("", &[]),
+        Kind::Scheduled {
+            starts_at,
+            rule_changes,
+        },
+    )
+    .await
+}
+
+async fn create_game(ctx: &Context, msg: &Message, mut args: Args, kind: Kind) -> CommandResult {
+    // ctx.data is only locked for short whiles, as every other command and game waits on it. Two
+    // games being made at once is dealt with once the new one is about to be stored instead
+    let guild_id = msg.guild_id.unwrap();
+    let (mut config, prefix, locale) = {
+        let data = ctx.data.read().await;
+        if data.get::<GameContainer>().is_some() {
+            drop(data);
+            msg.reply(ctx, "You cannot start a game if one is already running")
+                .await?;
+            return Ok(());
+        }
+
+        let mut config = data
+            .get::<GuildConfigContainer>()
+            .expect("ctx.data should always have a GuildConfigContainer")
+            .read()
+            .await
+            .get(guild_id);
+        // Status boards can be turned off for every server in the bot's configuration
+        if let Some(features) = data.get::<FeaturesContainer>() {
+            config.status_board &= features.status_board;
+        }
+        (
+            config,
+            prefix_from(&data, msg.guild_id).await,
+            i18n::locale_from(&data, msg.guild_id, None).await,
+        )
+    };
+    if let Kind::Scheduled { rule_changes, .. } = &kind {
+        for (setting, value) in rule_changes {
+            if let Err(err) = set_rule(&mut config, setting, value) {
+                msg.reply(ctx, err).await?;
+                return Ok(());
+            }
+        }
+    }
+
+    let meeting_room = args.single::<ChannelId>();
+    let meeting_room_id = meeting_room
+        .ok()
+        .or(config.meeting_room)
+        .unwrap_or(msg.channel_id);
+
+    let player_role = match args.single::<RoleId>().ok().or(config.player_role) {
+        Some(role) => Some(role),
+        // Servers that were set up before `config` existed use a role with this name
+        None => msg
+            .guild(ctx)
+            .await
+            .and_then(|g| g.role_by_name("The Guests").map(|r| r.id)),
+    };
+    let player_role_id = match player_role {
+        Some(role) => role,
+        None => {
+            msg.reply(
                 ctx,
                 format!(
-                    "{} has started a new game. You can join it by typing {}join",
-                    msg.author.name,
-                    data.get::<Prefix>().unwrap()
+                    "I don't know which role to give to players. Pass one to this command or set one with `{}config set playerrole <role>`",
+                    prefix
                 ),
             )
             .await?;
+            return Ok(());
+        }
+    };
+
+    let announcement_channel = args.single::<ChannelId>();
+    let announcement_channel_id = announcement_channel
+        .ok()
+        .or(config.announcement_channel)
+        .unwrap_or(msg.channel_id);
+
+    if let Ok(delete_rooms_category_on_game_end) = args.single::<bool>() {
+        config.delete_rooms_on_end = delete_rooms_category_on_game_end;
+    }
+
+    let host_id = match kind {
+        Kind::Automated { .. } => ctx.cache.current_user_id().await,
+        _ => msg.author.id,
+    };
+    let mut game = Game::new(
+        msg.id.0,
+        guild_id,
+        host_id,
+        meeting_room_id,
+        announcement_channel_id,
+        player_role_id,
+        config,
+    );
+
+    let announcement = match &kind {
+        Kind::Hosted => i18n::tr_with(
+            &locale,
+            "newgame.hosted",
+            &[("host", &msg.author.name), ("prefix", &prefix)],
+        ),
+        Kind::Automated { starts_in: None } => {
+            i18n::tr_with(&locale, "newgame.automated", &[("prefix", &prefix)])
+        }
+        Kind::Automated {
+            starts_in: Some(minutes),
+        } => i18n::tr_with(
+            &locale,
+            "newgame.automated_timed",
+            &[("minutes", minutes), ("prefix", &prefix)],
+        ),
+        Kind::Scheduled { starts_at, .. } => i18n::tr_with(
+            &locale,
+            "newgame.scheduled",
+            &[
+                ("host", &msg.author.name),
+                ("time", &format!("<t:{}:F>", starts_at.timestamp())),
+                ("prefix", &prefix),
+            ],
+        ),
+    };
+    match kind {
+        Kind::Hosted => {}
+        Kind::Automated { starts_in } => {
+            game.automate(starts_in.map(|minutes| Utc::now() + Duration::minutes(minutes.into())))
+        }
+        Kind::Scheduled { starts_at, .. } => {
+            // Games that start soon don't need a reminder on top of this message
+            game.schedule(starts_at, starts_at - Utc::now() <= lobby::reminder_lead())
+        }
     }
+
+    lobby::post(ctx, &mut game, msg.channel_id, &announcement).await?;
+
+    {
+        let mut data = ctx.data.write().await;
+        if data.get::<GameContainer>().is_none() {
+            // The tasks look the game up in ctx.data, which they can't do before it's in there
+            if game.is_automated() {
+                autopilot::spawn(ctx.clone(), &game);
+            }
+            if game.scheduled_start().is_some() {
+                lobby::follow_schedule(ctx.clone(), &game);
+            }
+            lobby::watch_controls(ctx.clone(), &game);
+            data.insert::<GameContainer>(Arc::new(RwLock::new(game)));
+            return Ok(());
+        }
+    }
+
+    // Someone else made a game while this one's lobby was being posted
+    lobby::close(ctx, &game).await;
+    msg.reply(ctx, "You cannot start a game if one is already running")
+        .await?;
     Ok(())
 }
@@ -17,6 +17,15 @@ pub async fn next_block(ctx: &Context, msg: &Message) -> CommandResult {
         return Ok(());
     }
 
+    if game.is_paused() {
+        msg.reply(
+            ctx,
+            "The game is paused until you decide what happens to the players that are gone.",
+        )
+        .await?;
+        return Ok(());
+    }
+
     *game = game.clone().transition_to_next_state(ctx).await;
     msg.channel_id
         .say(ctx, format!("☑️ New time block is {}", game.state_name()))
@@ -1,6 +1,6 @@
 use super::prelude::*;
 use crate::{
-    data::Prefix,
+    data,
     game::item::{MemoBook, Note},
     helpers::react::react_with,
 };
@@ -217,13 +217,7 @@ pub async fn rip_note(ctx: &Context, msg: &Message, mut args: Args) -> CommandRe
 
     let page = page.unwrap();
 
-    let prefix = ctx
-        .data
-        .read()
-        .await
-        .get::<Prefix>()
-        .expect("Prefix should always be in ctx.data")
-        .clone();
+    let prefix = data::prefix(ctx, msg.guild_id).await;
     if target.is_err() {
         msg.reply(
             ctx,
@@ -0,0 +1,91 @@
+use super::prelude::*;
+use crate::{helpers::perms, i18n};
+
+use serenity::model::{id::UserId, misc::Mentionable};
+use tracing::info;
+
+#[command]
+#[only_in(guilds)]
+#[description(
+    r#"Gives the seat of a player who has to leave a running game to someone else
+
+The replacement takes over the player's role, items, room and any choice they still had to make. Only the host can do this, or, in games I host myself, anyone who can manage the server.
+
+(Usage and Sample usage do not include the prefix, but it still must be used)"#
+)]
+#[usage("<player mention> <replacement mention>")]
+#[example("@KC#7788 @MyFriend")]
+#[checks(StandardGameCheck)]
+pub async fn replace(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let locale = i18n::locale(ctx, msg.guild_id, Some(msg.author.id)).await;
+    let (old, new) = match (args.single::<UserId>(), args.single::<UserId>()) {
+        (Ok(old), Ok(new)) => (old, new),
+        _ => {
+            msg.reply(ctx, i18n::tr(&locale, "replace.usage")).await?;
+            return Ok(());
+        }
+    };
+
+    // Whoever holds the game may be waiting on ctx.data, so these are looked up before locking it
+    let new_locale = i18n::locale(ctx, msg.guild_id, Some(new)).await;
+    let guild_locale = i18n::locale(ctx, msg.guild_id, None).await;
+    let game_guard = get_game_guard(ctx).await?;
+    let mut game = game_guard.write().await;
+
+    let is_host = msg.author.id == game.host();
+    let manages_server = msg
+        .member(ctx)
+        .await?
+        .permissions(ctx)
+        .await?
+        .manage_guild();
+    if !is_host && !(game.is_automated() && manages_server) {
+        msg.reply(ctx, i18n::tr(&locale, "replace.not_host"))
+            .await?;
+        return Ok(());
+    }
+
+    // Dead players don't have a seat to hand over anymore
+    if !game.player(old).map_or(false, |player| player.is_alive()) {
+        msg.reply(ctx, i18n::tr(&locale, "replace.not_alive"))
+            .await?;
+        return Ok(());
+    }
+    if new == game.host() || new.to_user(ctx).await?.bot {
+        msg.reply(ctx, i18n::tr(&locale, "replace.host_or_bot"))
+            .await?;
+        return Ok(());
+    }
+    if perms::sees_every_room(ctx, game.guild(), new).await? {
+        msg.reply(ctx, i18n::tr(&locale, "replace.sees_every_room"))
+            .await?;
+        return Ok(());
+    }
+
+    if let Err(err) = game.replace_player(ctx, old, new).await {
+        msg.reply(
+            ctx,
+            i18n::tr_with(&locale, "replace.failed", &[("error", &err)]),
+        )
+        .await?;
+        return Ok(());
+    }
+    info!("Replaced a player");
+
+    let room = game
+        .player(new)
+        .expect("replace_player should have seated the replacement")
+        .room();
+    let (new, old) = (new.mention(), old.mention());
+    let seats: [(&str, &dyn std::fmt::Display); 2] = [("new", &new), ("old", &old)];
+    room.say(ctx, i18n::tr_with(&new_locale, "replace.welcome", &seats))
+        .await?;
+    game.announcement_channel()
+        .say(
+            ctx,
+            i18n::tr_with(&guild_locale, "replace.announcement", &seats),
+        )
+        .await?;
+
+    Ok(())
+}
@@ -1,6 +1,6 @@
 use super::prelude::*;
 use crate::{
-    data::Prefix,
+    data,
     game::{Player, SecretMeeting},
     helpers::react::react_with,
 };
@@ -30,13 +30,7 @@ pub async fn show_meeting_log(ctx: &Context, msg: &Message, mut args: Args) -> C
     let game_guard = get_game_guard(ctx).await?;
     let game = game_guard.read().await;
 
-    let prefix = ctx
-        .data
-        .read()
-        .await
-        .get::<Prefix>()
-        .expect("Prefix should always be in ctx.data")
-        .clone();
+    let prefix = data::prefix(ctx, msg.guild_id).await;
 
     let day = match args.single::<u8>() {
         Ok(day) => day,
@@ -1,5 +1,5 @@
 use super::prelude::*;
-use crate::game::DeathCause;
+use crate::{game::DeathCause, themes};
 
 use rand::{self, distributions::Distribution};
 use serenity::model::id::UserId;
@@ -74,6 +74,7 @@ pub async fn stab(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult
     };
 
     if attacker_roll > target_roll {
+        let theme = themes::get(ctx, game.theme()).await;
         let target = match game.player_mut(target) {
             Some(target) => target,
             None => {
@@ -84,7 +85,7 @@ pub async fn stab(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult
         };
 
         target
-            .set_dead(DeathCause::Stab(msg.author.id), &ctx, channel.id)
+            .set_dead(DeathCause::Stab(msg.author.id), &ctx, channel.id, &theme)
             .await?;
 
         let new_target_perms = crate::helpers::perms::make_denied_override_for_user(target.id());
@@ -1,13 +1,19 @@
 use tracing::info;
 
 use super::prelude::*;
+use crate::{
+    helpers::preflight::{self, GameSetup},
+    i18n, lobby,
+};
+
+use serenity::model::id::ChannelId;
 
 #[command]
 #[only_in(guilds)]
 #[description("Starts a game if it has 6 players in it")]
 pub async fn start(ctx: &Context, msg: &Message) -> CommandResult {
-    let game = ctx.data.write().await;
-    let game = game.get::<GameContainer>();
+    // Starting the game looks up the players' locales in ctx.data, so it mustn't be kept locked
+    let game = ctx.data.read().await.get::<GameContainer>().cloned();
 
     match game {
         Some(game) => {
@@ -21,19 +27,7 @@ pub async fn start(ctx: &Context, msg: &Message) -> CommandResult {
                     .await
                     .map(|_| ())?;
             } else if game.can_start() {
-                msg.channel_id
-                    .say(ctx, "Starting the game...")
-                    .await
-                    .map(|_| ())?;
-
-                let res = game.clone().start(ctx).await;
-                match res {
-                    Ok(started_game) => *game = started_game,
-                    Err(err) => {
-                        msg.reply(ctx, format!("Couldn't start the game! Encountered the following error: \n\n```{}```", err)).await?;
-                        info!("{}", err);
-                    }
-                }
+                start_game(ctx, &mut game, msg.channel_id).await?;
             } else {
                 msg.reply_ping(ctx, "You can't start a game if there's less than 6 players")
                     .await?;
@@ -47,3 +41,39 @@ pub async fn start(ctx: &Context, msg: &Message) -> CommandResult {
 
     Ok(())
 }
+
+/// Starts `game` unless something keeps it from doing so, telling `channel` how it went
+///
+/// Returns whether the game started.
+pub async fn start_game(ctx: &Context, game: &mut Game, channel: ChannelId) -> CommandResult<bool> {
+    let locale = i18n::locale(ctx, Some(game.guild()), None).await;
+    let problems = preflight::check(ctx, &GameSetup::from_game(game), &locale).await?;
+    if !problems.is_empty() {
+        channel
+            .say(
+                ctx,
+                i18n::tr_with(
+                    &locale,
+                    "start.problems",
+                    &[("problems", &preflight::describe(&problems))],
+                ),
+            )
+            .await?;
+        return Ok(false);
+    }
+
+    channel.say(ctx, "Starting the game...").await?;
+
+    match game.clone().start(ctx).await {
+        Ok(started_game) => {
+            *game = started_game;
+            lobby::show_started(ctx, game).await;
+            Ok(true)
+        }
+        Err(err) => {
+            channel.say(ctx, format!("Couldn't start the game, so I cleaned up the channels and roles I made for it. Encountered the following error: \n\n```{}```", err)).await?;
+            info!("{}", err);
+            Ok(false)
+        }
+    }
+}
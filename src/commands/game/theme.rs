@@ -0,0 +1,87 @@
+use super::prelude::*;
+use crate::themes::ThemeContainer;
+
+use serenity::builder::CreateEmbed;
+use std::fmt::Write;
+
+#[command]
+#[only_in(guilds)]
+#[description(
+    r#"Lists the theme packs games can be played with, or, for the host, picks the one the game will be played with.
+
+(Usage and Sample usage do not include the prefix, but it still must be used)"#
+)]
+#[usage("[theme]")]
+#[example("scifi")]
+pub async fn theme(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let (game, themes) = {
+        let data = ctx.data.read().await;
+        (
+            data.get::<GameContainer>().cloned(),
+            data.get::<ThemeContainer>()
+                .cloned()
+                .expect("ctx.data should always have a ThemeContainer"),
+        )
+    };
+
+    let requested = match args.single::<String>() {
+        Ok(theme) => theme.to_lowercase(),
+        Err(_) => {
+            let mut list = String::new();
+            for theme in themes.iter() {
+                writeln!(list, "`{}` {}", theme.name(), theme.description)?;
+            }
+
+            let mut embed = CreateEmbed::default();
+            embed.title("Themes").description(list).colour(0x7289DA);
+            if let Some(game) = game {
+                embed.field(
+                    "Current game",
+                    format!("`{}`", game.read().await.theme()),
+                    false,
+                );
+            }
+
+            msg.channel_id
+                .send_message(ctx, |m| m.set_embed(embed))
+                .await?;
+            return Ok(());
+        }
+    };
+
+    let game = match game {
+        Some(game) => game,
+        None => {
+            msg.reply(
+                ctx,
+                "You can't pick a theme if there isn't a game to play it with!",
+            )
+            .await?;
+            return Ok(());
+        }
+    };
+    let mut game = game.write().await;
+
+    if msg.author.id != game.host() {
+        msg.reply(ctx, "Only the host can pick the theme of the game.")
+            .await?;
+    } else if game.is_started() {
+        msg.reply(
+            ctx,
+            "You can't change the theme of a game that has started!",
+        )
+        .await?;
+    } else if !themes.contains(&requested) {
+        msg.reply(ctx, format!("There's no theme called `{}`", requested))
+            .await?;
+    } else {
+        game.set_theme(requested.clone());
+        msg.reply(
+            ctx,
+            format!("The game will be played with the `{}` theme", requested),
+        )
+        .await?;
+    }
+
+    Ok(())
+}
@@ -1,4 +1,4 @@
-use crate::data::Prefix;
+use crate::data;
 
 use serenity::{
     framework::standard::{
@@ -26,11 +26,10 @@ pub async fn my_help(
     owners: HashSet<UserId>,
 ) -> CommandResult {
     let mut args_message = args.rest();
-    let data = context.data.read().await;
-    let prefix = data.get::<Prefix>().unwrap();
+    let prefix = data::prefix(context, msg.guild_id).await;
 
-    if args_message.starts_with(prefix) {
-        args_message = args_message.trim_start_matches(prefix);
+    if args_message.starts_with(&prefix) {
+        args_message = args_message.trim_start_matches(&prefix);
     }
 
     let args = Args::new(args_message, &[Delimiter::Single(' ')]);
@@ -0,0 +1,78 @@
+use super::prelude::*;
+use crate::{
+    data, i18n,
+    storage::{StorageContainer, UserLocaleContainer},
+};
+
+#[command]
+#[description(
+    r#"Shows or changes the language I speak with you in. This applies in every server.
+
+(Usage and Sample usage do not include the prefix, but it still must be used)"#
+)]
+#[usage("[locale]")]
+#[example("ro")]
+pub async fn language(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let locales = i18n::LOCALES.join(", ");
+
+    let requested = match args.single::<String>() {
+        Ok(locale) => locale.to_lowercase(),
+        Err(_) => {
+            let locale = i18n::locale(ctx, msg.guild_id, Some(msg.author.id)).await;
+            let prefix = data::prefix(ctx, msg.guild_id).await;
+            msg.reply(
+                ctx,
+                i18n::tr_with(
+                    &locale,
+                    "language.current",
+                    &[
+                        ("locale", &i18n::tr(&locale, "language.name")),
+                        ("prefix", &prefix),
+                        ("locales", &locales),
+                    ],
+                ),
+            )
+            .await?;
+            return Ok(());
+        }
+    };
+
+    if !i18n::is_supported(&requested) {
+        let locale = i18n::locale(ctx, msg.guild_id, Some(msg.author.id)).await;
+        msg.reply(
+            ctx,
+            i18n::tr_with(
+                &locale,
+                "language.unsupported",
+                &[("locale", &requested), ("locales", &locales)],
+            ),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    {
+        let data = ctx.data.read().await;
+        let storage = data
+            .get::<StorageContainer>()
+            .expect("ctx.data should always have a StorageContainer");
+
+        data.get::<UserLocaleContainer>()
+            .expect("ctx.data should always have a UserLocaleContainer")
+            .write()
+            .await
+            .set(storage, msg.author.id, requested.clone())?;
+    }
+
+    msg.reply(
+        ctx,
+        i18n::tr_with(
+            &requested,
+            "language.set",
+            &[("locale", &i18n::tr(&requested, "language.name"))],
+        ),
+    )
+    .await?;
+
+    Ok(())
+}
@@ -1,17 +1,23 @@
+mod config;
 mod delete_category;
 pub mod game;
 pub mod help;
+mod language;
 pub mod meta;
 pub mod random;
+mod setup;
 mod shutdown;
 pub mod stats;
 mod test_cmds;
 mod tos;
 
 use crate::commands::meta::*;
+use config::*;
 use delete_category::*;
 use game::*;
+use language::*;
 use random::*;
+use setup::*;
 use shutdown::*;
 use stats::*;
 use test_cmds::*;
@@ -25,7 +31,7 @@ use serenity::framework::standard::macros::group;
 pub struct Meta;
 
 #[group]
-#[commands(say, stats, delete_category, shutdown)]
+#[commands(say, stats, delete_category, shutdown, restart, language)]
 pub struct Random;
 
 #[group("Game Management")]
@@ -34,11 +40,16 @@ pub struct Random;
     join,
     leave,
     new_game,
+    auto_game,
+    schedule,
     end_game,
     start,
+    doctor,
+    theme,
     start_gathering,
     end_gathering,
-    next_block
+    next_block,
+    replace
 )]
 pub struct GameManagement;
 
@@ -63,9 +74,14 @@ pub struct PlayerInteractions;
 
 #[group("Game Information")]
 #[only_in(guilds)]
-#[commands(game_info, roles, role_info, rules, info)]
+#[commands(game_info, roles, role_info, rules, info, meeting_graph)]
 pub struct GameInformation;
 
+#[group]
+#[only_in(guilds)]
+#[commands(config, setup)]
+pub struct Configuration;
+
 #[group]
 #[commands(king_test, rev_test, confirm_murder, forceadd)]
 pub struct Tests;
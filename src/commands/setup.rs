@@ -0,0 +1,216 @@
+use super::prelude::*;
+use crate::{
+    helpers::perms,
+    storage::{GuildConfigContainer, StorageContainer},
+};
+
+use serenity::model::{
+    channel::{ChannelType, PermissionOverwrite},
+    guild::Guild,
+    id::{ChannelId, RoleId},
+    misc::Mentionable,
+};
+use std::fmt::Write;
+use tracing::info;
+
+static PLAYER_ROLE_NAME: &str = "The Guests";
+static MEETING_ROOM_NAME: &str = "meeting-room";
+static ANNOUNCEMENT_CHANNEL_NAME: &str = "announcements";
+
+#[command]
+#[only_in(guilds)]
+#[required_permissions("MANAGE_GUILD")]
+#[description(
+    r#"Prepares this server for games of Kingdom Royale.
+
+This makes the player role, the meeting room, an announcement channel and a category for the players' rooms, or reuses them if they already exist, and saves them as this server's defaults."#
+)]
+pub async fn setup(ctx: &Context, msg: &Message) -> CommandResult {
+    let guild = match msg.guild(ctx).await {
+        Some(guild) => guild,
+        None => return Err("setup: the guild wasn't in the cache".into()),
+    };
+
+    let me = guild.member(ctx, ctx.cache.current_user_id().await).await?;
+    let missing_permissions = perms::required_bot_permissions() - me.permissions(ctx).await?;
+    if !missing_permissions.is_empty() {
+        msg.reply(
+            ctx,
+            format!(
+                "I can't set up this server without the following permissions: `{:?}`",
+                missing_permissions
+            ),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let mut config = ctx
+        .data
+        .read()
+        .await
+        .get::<GuildConfigContainer>()
+        .expect("ctx.data should always have a GuildConfigContainer")
+        .read()
+        .await
+        .get(guild.id);
+
+    let mut report = String::new();
+
+    let configured_role = config.player_role.and_then(|id| guild.roles.get(&id));
+    let player_role = match configured_role.or_else(|| guild.role_by_name(PLAYER_ROLE_NAME)) {
+        Some(role) => {
+            writeln!(report, "• Reusing {} as the player role", role.mention())?;
+            role.clone()
+        }
+        None => {
+            let role = guild
+                .create_role(ctx, |r| {
+                    r.name(PLAYER_ROLE_NAME).hoist(false).mentionable(false)
+                })
+                .await?;
+            writeln!(report, "• Made {} to be the player role", role.mention())?;
+            role
+        }
+    };
+
+    if let Some((_, my_position)) = me.highest_role_info(&ctx.cache).await {
+        if my_position <= player_role.position {
+            writeln!(
+                report,
+                "⚠️ My highest role is below {}, move it above so I can give it to players",
+                player_role.mention()
+            )?;
+        }
+    }
+
+    let everyone = RoleId(guild.id.0);
+
+    let meeting_room = adopt_or_create_channel(
+        ctx,
+        &guild,
+        ChannelSpec {
+            configured: config.meeting_room,
+            name: MEETING_ROOM_NAME,
+            kind: ChannelType::Text,
+            overwrites: vec![perms::make_denied_override_for_role(player_role.id)],
+            what: "the meeting room",
+        },
+        &mut report,
+    )
+    .await?;
+
+    let announcement_channel = adopt_or_create_channel(
+        ctx,
+        &guild,
+        ChannelSpec {
+            configured: config.announcement_channel,
+            name: ANNOUNCEMENT_CHANNEL_NAME,
+            kind: ChannelType::Text,
+            overwrites: vec![perms::make_read_only_override_for_role(everyone)],
+            what: "the announcement channel",
+        },
+        &mut report,
+    )
+    .await?;
+
+    let rooms_category = adopt_or_create_channel(
+        ctx,
+        &guild,
+        ChannelSpec {
+            configured: config.rooms_category,
+            name: &config.rooms_category_name,
+            kind: ChannelType::Category,
+            overwrites: vec![perms::make_denied_override_for_role(everyone)],
+            what: "the category for the players' rooms",
+        },
+        &mut report,
+    )
+    .await?;
+
+    config.player_role = Some(player_role.id);
+    config.meeting_room = Some(meeting_room);
+    config.announcement_channel = Some(announcement_channel);
+    config.rooms_category = Some(rooms_category);
+
+    {
+        let data = ctx.data.read().await;
+        let storage = data
+            .get::<StorageContainer>()
+            .expect("ctx.data should always have a StorageContainer");
+
+        data.get::<GuildConfigContainer>()
+            .expect("ctx.data should always have a GuildConfigContainer")
+            .write()
+            .await
+            .update(storage, guild.id, |c| *c = config)?;
+    }
+
+    info!("Set up guild {}", guild.id);
+
+    msg.channel_id
+        .send_message(ctx, |m| {
+            m.embed(|e| {
+                e.title("Server setup complete")
+                    .description(report)
+                    .footer(|f| f.text("These were saved as this server's defaults"))
+                    .colour(0x0dd910)
+            })
+        })
+        .await?;
+
+    Ok(())
+}
+
+/// Describes a channel the setup needs
+struct ChannelSpec<'a> {
+    /// The channel already in the server's configuration, if any
+    configured: Option<ChannelId>,
+    name: &'a str,
+    kind: ChannelType,
+    overwrites: Vec<PermissionOverwrite>,
+    /// How to refer to the channel in the report
+    what: &'a str,
+}
+
+/// Finds a channel matching `spec`, either by the configured ID or by name, and makes it if it
+/// can't find one. Either way, the overwrites in `spec` are applied to it.
+async fn adopt_or_create_channel(
+    ctx: &Context,
+    guild: &Guild,
+    spec: ChannelSpec<'_>,
+    report: &mut String,
+) -> CommandResult<ChannelId> {
+    let existing = spec
+        .configured
+        .and_then(|id| guild.channels.get(&id))
+        .or_else(|| {
+            guild
+                .channels
+                .values()
+                .find(|c| c.kind == spec.kind && c.name.eq_ignore_ascii_case(spec.name))
+        });
+
+    let channel = match existing {
+        Some(channel) => {
+            writeln!(report, "• Reusing {} as {}", channel.mention(), spec.what)?;
+            for overwrite in &spec.overwrites {
+                channel.create_permission(ctx, overwrite).await?;
+            }
+            channel.id
+        }
+        None => {
+            let channel = guild
+                .create_channel(ctx, |c| {
+                    c.name(spec.name)
+                        .kind(spec.kind)
+                        .permissions(spec.overwrites.clone())
+                })
+                .await?;
+            writeln!(report, "• Made {} to be {}", channel.mention(), spec.what)?;
+            channel.id
+        }
+    };
+
+    Ok(channel)
+}
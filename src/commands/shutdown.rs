@@ -1,19 +1,28 @@
-use crate::bot::ShardManagerContainer;
+use crate::shutdown::shut_down;
 
-use super::game::end_game_impl;
 use super::prelude::*;
 
 #[command]
 #[owners_only]
+#[description(
+    "Stops the bot. A running game is saved instead of ended, and its host can resume it once the bot is back"
+)]
 pub async fn shutdown(ctx: &Context, msg: &Message) -> CommandResult {
-    let _ = end_game_impl(ctx, msg, true).await;
+    msg.reply(ctx, "Shutting down...").await?;
+    shut_down(&ctx.data, &ctx.http, false).await;
 
-    let data = ctx.data.read().await;
-    let shard_manager = data
-        .get::<ShardManagerContainer>()
-        .expect("ctx.data should always have a ShardManagerContainer");
+    Ok(())
+}
 
-    shard_manager.lock().await.shutdown_all().await;
+#[command]
+#[owners_only]
+#[description(
+    "Stops the bot and starts it again. A running game is saved instead of ended, and its host can resume it once the bot is back"
+)]
+pub async fn restart(ctx: &Context, msg: &Message) -> CommandResult {
+    msg.reply(ctx, "Restarting, I'll be back shortly...")
+        .await?;
+    shut_down(&ctx.data, &ctx.http, true).await;
 
     Ok(())
 }
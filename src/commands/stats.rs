@@ -34,27 +34,8 @@ pub async fn stats(ctx: &Context, msg: &Message) -> CommandResult {
         contents
     };
 
-    // This SO answers describes what PSS is, and why I use it <https://stackoverflow.com/a/13754307>
     #[cfg(target_os = "linux")]
-    let (uss, pss, rss) = {
-        use procfs::process::Process;
-
-        let myself = Process::myself()?;
-        //let _maps = myself.maps()?;
-        let smaps = myself.smaps()?;
-
-        //info!("{:?}", _maps);
-
-        let mut pss: u64 = 0;
-        let mut uss: u64 = 0;
-        for (_, data) in smaps {
-            pss += data.map.get("Pss").unwrap_or(&0);
-            uss += data.map.get("Private_Clean").unwrap_or(&0)
-                + data.map.get("Private_Dirty").unwrap_or(&0);
-        }
-
-        (uss, pss, myself.stat.rss)
-    };
+    let memory = stats::memory_usage()?;
 
     let startup_time = *ctx
         .data
@@ -103,9 +84,9 @@ pub async fn stats(ctx: &Context, msg: &Message) -> CommandResult {
             "Memory usage",
             format!(
                 "PSS: {}\nRSS: {}\n USS(no shared): {}",
-                pss.file_size(size_options::BINARY).unwrap(),
-                uss.file_size(size_options::BINARY).unwrap(),
-                rss.file_size(size_options::BINARY).unwrap()
+                memory.pss.file_size(size_options::BINARY).unwrap(),
+                memory.rss.file_size(size_options::BINARY).unwrap(),
+                memory.uss.file_size(size_options::BINARY).unwrap()
             ),
             true,
         );
@@ -1,14 +1,13 @@
 use crate::{
     commands::prelude::*,
-    data::Cdn,
-    game::{King, Player, RoleHolder, RoleName},
+    game::{item::WATCH_COLOURS, GameRules, King, Player, RoleHolder, RoleName},
     helpers::{
-        choose_target::{build_embed_for_target_choice, Players},
+        choose_target::{build_embed_for_target_choice, CompositeCache, Players},
         react::react_with,
     },
 };
 
-use serenity::model::id::UserId;
+use serenity::model::id::{ChannelId, UserId};
 
 #[command("testk")]
 #[description("Used to test the algorithm that creates the embed for the time when the king-like player needs to choose one for murder")]
@@ -28,21 +27,13 @@ struct MockPlayers {
 }
 
 impl MockPlayers {
-    async fn new(ctx: &Context, ids: Vec<UserId>) -> Self {
+    fn new(room: ChannelId, ids: Vec<UserId>) -> Self {
         let mut players = vec![];
+        let rules = GameRules::default();
 
         for (idx, id) in ids.iter().enumerate() {
-            let mut player = Player::new(
-                *id,
-                RoleHolder::King(King),
-                ctx.data
-                    .read()
-                    .await
-                    .get::<Cdn>()
-                    .copied()
-                    .expect("There must always be a CDN in ctx.data"),
-                "lol".to_string(),
-            );
+            let watch_colour = WATCH_COLOURS[idx % WATCH_COLOURS.len()].to_string();
+            let mut player = Player::new(*id, RoleHolder::King(King), room, watch_colour, &rules);
 
             if idx % 2 == 0 {
                 player.set_dead_mock();
@@ -78,9 +69,9 @@ async fn generic_test(
         player_ids.push(user_id);
     }
 
-    let players = MockPlayers::new(ctx, player_ids).await;
+    let players = MockPlayers::new(msg.channel_id, player_ids);
 
-    let embed = build_embed_for_target_choice(
+    let choice = build_embed_for_target_choice(
         ctx,
         &players,
         if role_kind.is_king_like() {
@@ -88,12 +79,16 @@ async fn generic_test(
         } else {
             "Please select a target for 「 Assassination 」"
         },
+        &CompositeCache::default(),
     )
     .await?;
 
     let sent_msg = msg
         .channel_id
-        .send_message(ctx, |m| m.set_embed(embed))
+        .send_message(ctx, |m| {
+            m.set_embed(choice.embed.clone())
+                .add_file(choice.attachment())
+        })
         .await?;
 
     react_with(ctx, &sent_msg, &["1️⃣", "2️⃣", "3️⃣", "4️⃣", "5️⃣", "6️⃣"]).await?;
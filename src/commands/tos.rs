@@ -4,9 +4,9 @@ use super::prelude::*;
 #[description("Shows the TOS")]
 pub async fn tos(ctx: &Context, msg: &Message) -> CommandResult {
     msg.channel_id.say(ctx, r#"
-By using this bot you agree for your Discord user ID to be stored in the bot's RAM for the duration of a game.
+By using this bot you agree for your Discord user ID to be stored for the duration of a game. Snapshots of games, which include the players' IDs, their items and notes, are stored on disk so games can survive the bot restarting.
 
-Messages are never stored on disk.
+Messages are never stored on disk. Server settings changed with `config`, including the IDs of the channels and roles they refer to, are stored on disk until they are reset.
     "#).await?;
     Ok(())
 }
@@ -0,0 +1,727 @@
+//! The configuration of the bot itself, as opposed to the per-server one changed with `!config`
+//!
+//! It's read from a TOML file, `maid.toml` unless `--config` or `MAID_CONFIG` point somewhere else,
+//! and every setting can be overridden by an environment variable named after its path, e.g.
+//! `MAID_RULES_FOOD_PORTIONS` for `rules.food_portions`. Variables in `.env` count as well.
+//!
+//! Loading doesn't stop at the first mistake, every problem is collected along with the path of
+//! the setting it's about, so they can all be fixed in one go.
+
+use crate::game::{AssistantDefault, GameRules, TargetDefault};
+
+use serde::de::DeserializeOwned;
+use serenity::{model::id::UserId, prelude::TypeMapKey};
+use std::{
+    collections::HashSet,
+    env, fmt, fs,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+use toml::{value::Table, Value};
+use tracing_subscriber::EnvFilter;
+
+/// Where the configuration is read from when neither `--config` nor `MAID_CONFIG` are given
+pub const DEFAULT_PATH: &str = "maid.toml";
+
+#[derive(Debug)]
+pub struct Config {
+    pub token: String,
+    pub prefix: String,
+    /// Replaces the owners of the Discord application, unless it's empty
+    pub owners: HashSet<UserId>,
+    pub storage_path: PathBuf,
+    pub log: LogConfig,
+    /// The rules of the servers that didn't change them with `!config`
+    pub rules: GameRules,
+    pub features: Features,
+    pub images: ImageConfig,
+    pub metrics: MetricsConfig,
+}
+
+#[derive(Debug)]
+pub struct LogConfig {
+    /// A `tracing` filter, like `info` or `kingdom_royale_maid=debug,serenity=warn`
+    pub level: String,
+    /// Whether to colour the logs, which never happens when they're written to files
+    pub ansi: bool,
+    pub format: LogFormat,
+    /// Where to write the logs instead of the terminal
+    pub directory: Option<PathBuf>,
+    /// How often to start a new file in `directory`
+    pub rotation: Rotation,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    /// One line per event, meant to be read by people
+    Human,
+    /// One JSON object per event, along with the spans it happened in
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "human" => Ok(Self::Human),
+            "json" => Ok(Self::Json),
+            _ => Err("this needs to be either \"human\" or \"json\""),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rotation {
+    Hourly,
+    Daily,
+    Never,
+}
+
+impl FromStr for Rotation {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "hourly" => Ok(Self::Hourly),
+            "daily" => Ok(Self::Daily),
+            "never" => Ok(Self::Never),
+            _ => Err("this needs to be \"hourly\", \"daily\" or \"never\""),
+        }
+    }
+}
+
+/// Parts of the bot that can be turned off for everyone
+#[derive(Debug, Clone, Copy)]
+pub struct Features {
+    /// Asking the hosts of games that were running when the bot stopped whether to resume them
+    pub recovery: bool,
+    /// Keeping downloaded avatars on disk
+    pub avatar_cache: bool,
+    /// Letting servers turn on status boards
+    pub status_board: bool,
+}
+
+impl Default for Features {
+    fn default() -> Self {
+        Self {
+            recovery: true,
+            avatar_cache: true,
+            status_board: true,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ImageConfig {
+    /// How much space downloaded avatars can take up on disk, in megabytes
+    pub avatar_cache_size_mb: u64,
+}
+
+#[derive(Debug)]
+pub struct MetricsConfig {
+    /// Where to serve metrics for Prometheus, they aren't served if it's `None`
+    pub address: Option<SocketAddr>,
+}
+
+impl ImageConfig {
+    pub fn avatar_cache_size(&self) -> u64 {
+        self.avatar_cache_size_mb * 1024 * 1024
+    }
+}
+
+/// Something wrong with a setting
+#[derive(Debug)]
+pub struct Problem {
+    /// The path of the setting, like `rules.food_portions`, or of the file for problems with the
+    /// whole file
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+pub struct FeaturesContainer;
+
+impl TypeMapKey for FeaturesContainer {
+    type Value = Features;
+}
+
+impl Config {
+    /// Loads the configuration from `path`, or from the default location if it's `None`
+    ///
+    /// The file at the default location doesn't have to exist, the environment can hold everything
+    /// that's needed. The token only has to be set if `needs_token` is, as only connecting to
+    /// Discord needs it.
+    pub fn load(path: Option<&Path>, needs_token: bool) -> Result<Self, Vec<Problem>> {
+        // `.env` files are optional
+        let _ = dotenv::dotenv();
+
+        let (path, required) = match path
+            .map(Path::to_path_buf)
+            .or_else(|| env::var_os("MAID_CONFIG").map(PathBuf::from))
+        {
+            Some(path) => (path, true),
+            None => (PathBuf::from(DEFAULT_PATH), false),
+        };
+
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => Some(text),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound && !required => None,
+            Err(err) => {
+                return Err(vec![Problem {
+                    path: path.display().to_string(),
+                    message: format!("couldn't read the file: {}", err),
+                }])
+            }
+        };
+
+        Self::parse(
+            &path,
+            text.as_deref(),
+            &|var| env::var(var).ok(),
+            needs_token,
+        )
+    }
+
+    /// Reads the configuration out of `text`, the contents of the file at `path`, overriding it
+    /// with the variables `env` knows about
+    fn parse(
+        path: &Path,
+        text: Option<&str>,
+        env: &dyn Fn(&str) -> Option<String>,
+        needs_token: bool,
+    ) -> Result<Self, Vec<Problem>> {
+        let mut reader = Reader {
+            problems: vec![],
+            env,
+        };
+
+        let mut file = match text.map(toml::from_str::<Table>) {
+            Some(Ok(table)) => table,
+            Some(Err(err)) => {
+                return Err(vec![Problem {
+                    path: path.display().to_string(),
+                    message: err.to_string(),
+                }])
+            }
+            None => Table::new(),
+        };
+
+        // `DISCORD_TOKEN` is what the bot used before it had a configuration file
+        let token = reader
+            .take::<String>(&mut file, "token")
+            .or_else(|| (reader.env)("DISCORD_TOKEN").filter(|token| !token.is_empty()));
+        let token_file = reader.take::<PathBuf>(&mut file, "token_file");
+        let prefix = reader
+            .take(&mut file, "prefix")
+            .unwrap_or_else(|| "!".to_string());
+        let owners = reader.owners(&mut file);
+        let storage_path = reader
+            .take(&mut file, "storage_path")
+            .unwrap_or_else(|| PathBuf::from("storage"));
+
+        let mut section = reader.section(&mut file, "log");
+        let log = LogConfig {
+            level: reader
+                .take(&mut section, "log.level")
+                .or_else(|| (reader.env)("RUST_LOG").filter(|level| !level.is_empty()))
+                .unwrap_or_else(|| "info".to_string()),
+            ansi: reader.take(&mut section, "log.ansi").unwrap_or(true),
+            format: reader
+                .parse(&mut section, "log.format")
+                .unwrap_or(LogFormat::Human),
+            directory: reader.take(&mut section, "log.directory"),
+            rotation: reader
+                .parse(&mut section, "log.rotation")
+                .unwrap_or(Rotation::Daily),
+        };
+        reader.finish(section, "log");
+
+        let mut rules = GameRules::default();
+        let mut section = reader.section(&mut file, "rules");
+        if let Some(food) = reader.take(&mut section, "rules.food_portions") {
+            rules.food_portions = food;
+        }
+        if let Some(max_notes) = reader.take(&mut section, "rules.max_notes") {
+            rules.max_notes = max_notes;
+        }
+        if let Some(timeout) = reader.take(&mut section, "rules.decision_timeout_minutes") {
+            rules.decision_timeout_minutes = timeout;
+        }
+        if let Some(reminder) = reader.take(&mut section, "rules.decision_reminder_minutes") {
+            rules.decision_reminder_minutes = reminder;
+        }
+        if let Some(block) = reader.take(&mut section, "rules.block_minutes") {
+            rules.block_minutes = block;
+        }
+        let mut defaults = reader.section(&mut section, "rules.decision_defaults");
+        let decision_defaults = &mut rules.decision_defaults;
+        if let Some(target) =
+            reader.parse::<TargetDefault>(&mut defaults, "rules.decision_defaults.murder_target")
+        {
+            decision_defaults.murder_target = target;
+        }
+        if let Some(assistant) =
+            reader.parse::<AssistantDefault>(&mut defaults, "rules.decision_defaults.assistant")
+        {
+            decision_defaults.assistant = assistant;
+        }
+        if let Some(target) = reader.parse::<TargetDefault>(
+            &mut defaults,
+            "rules.decision_defaults.assassination_target",
+        ) {
+            decision_defaults.assassination_target = target;
+        }
+        reader.finish(defaults, "rules.decision_defaults");
+        reader.finish(section, "rules");
+
+        let mut features = Features::default();
+        let mut section = reader.section(&mut file, "features");
+        if let Some(recovery) = reader.take(&mut section, "features.recovery") {
+            features.recovery = recovery;
+        }
+        if let Some(avatar_cache) = reader.take(&mut section, "features.avatar_cache") {
+            features.avatar_cache = avatar_cache;
+        }
+        if let Some(status_board) = reader.take(&mut section, "features.status_board") {
+            features.status_board = status_board;
+        }
+        reader.finish(section, "features");
+
+        let mut section = reader.section(&mut file, "images");
+        let images = ImageConfig {
+            avatar_cache_size_mb: reader
+                .take(&mut section, "images.avatar_cache_size_mb")
+                .unwrap_or(64),
+        };
+        reader.finish(section, "images");
+
+        let mut section = reader.section(&mut file, "metrics");
+        let metrics = MetricsConfig {
+            address: reader.parse(&mut section, "metrics.address"),
+        };
+        reader.finish(section, "metrics");
+
+        reader.finish(file, "");
+
+        let token = if needs_token {
+            reader.token(token, token_file)
+        } else {
+            token
+        };
+        let config = Self {
+            token: token.unwrap_or_default(),
+            prefix,
+            owners,
+            storage_path,
+            log,
+            rules,
+            features,
+            images,
+            metrics,
+        };
+        config.validate(&mut reader.problems);
+
+        if reader.problems.is_empty() {
+            Ok(config)
+        } else {
+            Err(reader.problems)
+        }
+    }
+
+    /// Checks the settings that have the right type but still don't make sense
+    fn validate(&self, problems: &mut Vec<Problem>) {
+        let mut problem = |path: &str, message: &str| {
+            problems.push(Problem {
+                path: path.to_string(),
+                message: message.to_string(),
+            })
+        };
+
+        if self.prefix.is_empty() || self.prefix.contains(char::is_whitespace) {
+            problem("prefix", "the prefix can't be empty or have spaces in it");
+        }
+        if self.storage_path.is_file() {
+            problem("storage_path", "this is a file, it needs to be a directory");
+        }
+        if let Err(err) = EnvFilter::try_new(&self.log.level) {
+            problem("log.level", &format!("this isn't a valid filter: {}", err));
+        }
+        if self.log.directory.as_deref().map_or(false, Path::is_file) {
+            problem(
+                "log.directory",
+                "this is a file, it needs to be a directory",
+            );
+        }
+        if self.rules.decision_timeout_minutes == 0 {
+            problem(
+                "rules.decision_timeout_minutes",
+                "players need at least a minute to decide",
+            );
+        }
+        if self.rules.decision_reminder_minutes >= self.rules.decision_timeout_minutes {
+            problem(
+                "rules.decision_reminder_minutes",
+                "reminders have to be sent before the decision times out, use 0 to turn them off",
+            );
+        }
+        if self.rules.max_notes == 0 {
+            problem(
+                "rules.max_notes",
+                "memo books need room for at least one note",
+            );
+        }
+        if self.rules.block_minutes == 0 {
+            problem(
+                "rules.block_minutes",
+                "time blocks need to last at least a minute",
+            );
+        }
+        if self.images.avatar_cache_size_mb == 0 {
+            problem(
+                "images.avatar_cache_size_mb",
+                "the cache needs some space, set features.avatar_cache to false to turn it off",
+            );
+        }
+    }
+}
+
+struct Reader<'a> {
+    problems: Vec<Problem>,
+    env: &'a dyn Fn(&str) -> Option<String>,
+}
+
+impl Reader<'_> {
+    /// Takes the setting at `path` out of `table`, which holds the settings of its section
+    ///
+    /// The environment variable for the setting takes precedence, it's read as a TOML value if it
+    /// looks like one, and as a string otherwise.
+    fn take<T: DeserializeOwned>(&mut self, table: &mut Table, path: &str) -> Option<T> {
+        let from_file = table.remove(key_of(path));
+
+        let var = env_var(path);
+        if let Some(raw) = (self.env)(&var).filter(|raw| !raw.is_empty()) {
+            let value = toml::from_str::<Table>(&format!("value = {}", raw))
+                .ok()
+                .and_then(|mut table| table.remove("value"))
+                .unwrap_or_else(|| Value::String(raw.clone()));
+
+            return match value.try_into() {
+                Ok(value) => Some(value),
+                // Strings that happen to look like something else, like a prefix of `1`
+                Err(err) => match Value::String(raw).try_into() {
+                    Ok(value) => Some(value),
+                    Err(_) => {
+                        self.problem(&format!("{} (from {})", path, var), err);
+                        None
+                    }
+                },
+            };
+        }
+
+        match from_file?.try_into() {
+            Ok(value) => Some(value),
+            Err(err) => {
+                self.problem(path, err);
+                None
+            }
+        }
+    }
+
+    /// Like [`Self::take`], but for settings written the way `!config set` takes them
+    fn parse<T>(&mut self, table: &mut Table, path: &str) -> Option<T>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        let raw = self.take::<String>(table, path)?;
+        match raw.parse() {
+            Ok(value) => Some(value),
+            Err(err) => {
+                self.problem(path, err);
+                None
+            }
+        }
+    }
+
+    /// Takes the section at `path` out of `table`, as a table of its own
+    fn section(&mut self, table: &mut Table, path: &str) -> Table {
+        match table.remove(key_of(path)) {
+            Some(Value::Table(section)) => section,
+            Some(_) => {
+                self.problem(path, "this needs to be a section, like [section]");
+                Table::new()
+            }
+            None => Table::new(),
+        }
+    }
+
+    /// Reports everything that's left in the section at `path` as unknown
+    fn finish(&mut self, table: Table, path: &str) {
+        for key in table.keys() {
+            let key = if path.is_empty() {
+                key.clone()
+            } else {
+                format!("{}.{}", path, key)
+            };
+            self.problem(&key, "there's no such setting");
+        }
+    }
+
+    /// Owners can be written as numbers or as strings, as IDs are copied out of Discord as text
+    fn owners(&mut self, table: &mut Table) -> HashSet<UserId> {
+        let owners = match self.take::<Value>(table, "owners") {
+            Some(Value::Array(owners)) => owners,
+            // `MAID_OWNERS` is more convenient as a list separated by commas
+            Some(Value::String(owners)) => owners
+                .split(',')
+                .map(|id| Value::String(id.trim().to_string()))
+                .collect(),
+            Some(Value::Integer(owner)) => vec![Value::Integer(owner)],
+            Some(_) => {
+                self.problem("owners", "this needs to be a list of user IDs");
+                return HashSet::new();
+            }
+            None => return HashSet::new(),
+        };
+
+        let mut ids = HashSet::new();
+        for (idx, owner) in owners.into_iter().enumerate() {
+            let id = match &owner {
+                Value::Integer(id) if *id > 0 => Some(*id as u64),
+                Value::String(id) => id.parse::<u64>().ok().filter(|id| *id > 0),
+                _ => None,
+            };
+            match id {
+                Some(id) => {
+                    ids.insert(UserId(id));
+                }
+                None => self.problem(
+                    &format!("owners[{}]", idx),
+                    format!("{} isn't a user ID", owner),
+                ),
+            }
+        }
+
+        ids
+    }
+
+    /// Picks the token out of the two places it can be set in, `token` winning
+    fn token(&mut self, token: Option<String>, token_file: Option<PathBuf>) -> Option<String> {
+        // The example configuration has an empty token
+        if let Some(token) = token.filter(|token| !token.is_empty()) {
+            return Some(token);
+        }
+
+        let token_file = match token_file {
+            Some(path) => path,
+            None => {
+                self.problem(
+                    "token",
+                    "the bot needs a token, set token, token_file or DISCORD_TOKEN",
+                );
+                return None;
+            }
+        };
+
+        match fs::read_to_string(&token_file) {
+            Ok(token) if !token.trim().is_empty() => Some(token.trim().to_string()),
+            Ok(_) => {
+                self.problem("token_file", format!("{} is empty", token_file.display()));
+                None
+            }
+            Err(err) => {
+                self.problem(
+                    "token_file",
+                    format!("couldn't read {}: {}", token_file.display(), err),
+                );
+                None
+            }
+        }
+    }
+
+    fn problem(&mut self, path: &str, message: impl fmt::Display) {
+        self.problems.push(Problem {
+            path: path.to_string(),
+            message: message.to_string(),
+        });
+    }
+}
+
+/// The last part of `path`, i.e. its key in its section
+fn key_of(path: &str) -> &str {
+    path.rsplit('.').next().unwrap_or(path)
+}
+
+/// The environment variable that overrides the setting at `path`
+fn env_var(path: &str) -> String {
+    format!("MAID_{}", path.replace('.', "_").to_uppercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn parse(text: &str, env: &[(&str, &str)]) -> Result<Config, Vec<Problem>> {
+        let env = env
+            .iter()
+            .map(|(var, value)| (var.to_string(), value.to_string()))
+            .collect::<HashMap<_, _>>();
+        Config::parse(
+            Path::new("maid.toml"),
+            Some(text),
+            &|var| env.get(var).cloned(),
+            true,
+        )
+    }
+
+    fn paths(problems: Vec<Problem>) -> Vec<String> {
+        let mut paths = problems
+            .into_iter()
+            .map(|problem| problem.path)
+            .collect::<Vec<_>>();
+        paths.sort();
+        paths
+    }
+
+    #[test]
+    fn reads_every_section() {
+        let config = parse(
+            r#"
+            token = "abc.def"
+            prefix = "?"
+            owners = [1234, "5678"]
+
+            [log]
+            level = "debug"
+            format = "json"
+            directory = "logs"
+
+            [rules]
+            food_portions = 3
+            block_minutes = 15
+
+            [rules.decision_defaults]
+            murder_target = "random"
+
+            [features]
+            recovery = false
+
+            [images]
+            avatar_cache_size_mb = 10
+
+            [metrics]
+            address = "127.0.0.1:9184"
+            "#,
+            &[],
+        )
+        .unwrap();
+
+        assert_eq!(config.token, "abc.def");
+        assert_eq!(config.prefix, "?");
+        assert_eq!(config.owners.len(), 2);
+        assert!(config.owners.contains(&UserId(5678)));
+        assert_eq!(config.log.level, "debug");
+        assert_eq!(config.log.format, LogFormat::Json);
+        assert_eq!(config.log.directory, Some(PathBuf::from("logs")));
+        assert_eq!(config.log.rotation, Rotation::Daily);
+        assert_eq!(config.rules.food_portions, 3);
+        assert_eq!(config.rules.block_minutes, 15);
+        assert_eq!(
+            config.rules.decision_defaults.murder_target,
+            TargetDefault::Random
+        );
+        assert!(!config.features.recovery);
+        assert!(config.features.avatar_cache);
+        assert_eq!(config.images.avatar_cache_size(), 10 * 1024 * 1024);
+        assert_eq!(
+            config.metrics.address,
+            Some(SocketAddr::from(([127, 0, 0, 1], 9184)))
+        );
+    }
+
+    #[test]
+    fn environment_overrides_the_file() {
+        let config = parse(
+            "prefix = \"?\"\n[rules]\nfood_portions = 3",
+            &[
+                ("DISCORD_TOKEN", "from.env"),
+                ("MAID_PREFIX", "1"),
+                ("MAID_RULES_FOOD_PORTIONS", "5"),
+                ("MAID_OWNERS", "12, 34"),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(config.token, "from.env");
+        assert_eq!(config.prefix, "1");
+        assert_eq!(config.rules.food_portions, 5);
+        assert_eq!(config.owners.len(), 2);
+    }
+
+    #[test]
+    fn reports_every_problem() {
+        let problems = parse(
+            r#"
+            token = "abc"
+            prefix = "! "
+            owners = [12, "me"]
+            colour = "blue"
+
+            [log]
+            rotation = "weekly"
+
+            [rules]
+            food_portions = 300
+            decision_timeout_minutes = 5
+            decision_reminder_minutes = 10
+            max_notes = 0
+            block_minutes = 0
+
+            [rules.decision_defaults]
+            assistant = "maybe"
+
+            [images]
+            avatar_cache_size_mb = "big"
+            "#,
+            &[("MAID_FEATURES_RECOVERY", "sometimes")],
+        )
+        .unwrap_err();
+
+        assert_eq!(
+            paths(problems),
+            [
+                "colour",
+                "features.recovery (from MAID_FEATURES_RECOVERY)",
+                "images.avatar_cache_size_mb",
+                "log.rotation",
+                "owners[1]",
+                "prefix",
+                "rules.block_minutes",
+                "rules.decision_defaults.assistant",
+                "rules.decision_reminder_minutes",
+                "rules.food_portions",
+                "rules.max_notes",
+            ]
+        );
+    }
+
+    #[test]
+    fn needs_a_token() {
+        let problems = parse("", &[]).unwrap_err();
+        assert_eq!(paths(problems), ["token"]);
+    }
+
+    #[test]
+    fn offline_commands_dont_need_a_token() {
+        let config = Config::parse(Path::new("maid.toml"), Some(""), &|_| None, false).unwrap();
+        assert_eq!(config.token, "");
+    }
+}
@@ -1,5 +1,7 @@
+use crate::storage::GuildConfigContainer;
+
 pub use reqwest::Client as Reqwest;
-use serenity::{model::id::ChannelId, prelude::*};
+use serenity::{model::id::GuildId, prelude::*};
 
 pub struct ReqwestClient;
 
@@ -7,18 +9,31 @@ impl TypeMapKey for ReqwestClient {
     type Value = Reqwest;
 }
 
-pub struct Cdn;
-
-impl TypeMapKey for Cdn {
-    type Value = ChannelId;
-}
-
 pub struct Prefix;
 
 impl TypeMapKey for Prefix {
     type Value = String;
 }
 
+/// Gets the prefix used in `guild`, i.e. the one set with `!config` or, if there's none, the one
+/// the bot was started with
+pub async fn prefix(ctx: &Context, guild: Option<GuildId>) -> String {
+    prefix_from(&*ctx.data.read().await, guild).await
+}
+
+/// Like [`prefix`] but for when `ctx.data` is already locked
+pub async fn prefix_from(data: &TypeMap, guild: Option<GuildId>) -> String {
+    if let (Some(guild), Some(configs)) = (guild, data.get::<GuildConfigContainer>()) {
+        if let Some(prefix) = configs.read().await.get(guild).prefix {
+            return prefix;
+        }
+    }
+
+    data.get::<Prefix>()
+        .cloned()
+        .expect("Prefix should always be in ctx.data")
+}
+
 pub mod stats {
     use serenity::prelude::RwLock;
     use std::{collections::HashMap, sync::Arc};
@@ -54,6 +69,41 @@ pub mod stats {
         }
     }
 
+    /// How much memory the bot uses, in bytes
+    #[cfg(target_os = "linux")]
+    pub struct MemoryUsage {
+        /// Memory only the bot uses
+        pub uss: u64,
+        /// Memory the bot uses, with shared memory split between the processes sharing it
+        pub pss: u64,
+        pub rss: u64,
+    }
+
+    /// Reads how much memory the bot uses from `/proc`
+    ///
+    /// This SO answers describes what PSS is, and why it's used <https://stackoverflow.com/a/13754307>
+    #[cfg(target_os = "linux")]
+    pub fn memory_usage() -> procfs::ProcResult<MemoryUsage> {
+        use procfs::process::Process;
+
+        let myself = Process::myself()?;
+        let smaps = myself.smaps()?;
+
+        let mut pss: u64 = 0;
+        let mut uss: u64 = 0;
+        for (_, data) in smaps {
+            pss += data.map.get("Pss").unwrap_or(&0);
+            uss += data.map.get("Private_Clean").unwrap_or(&0)
+                + data.map.get("Private_Dirty").unwrap_or(&0);
+        }
+
+        Ok(MemoryUsage {
+            uss,
+            pss,
+            rss: myself.stat.rss_bytes() as u64,
+        })
+    }
+
     #[cfg(target_os = "linux")]
     pub struct SystemVersion;
 
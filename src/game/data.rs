@@ -1,5 +1,7 @@
+use crate::themes::Theme;
+
+use serde::{Deserialize, Serialize};
 use serenity::{model::id::UserId, prelude::Mentionable};
-use std::fmt;
 
 #[derive(Copy, Clone, PartialEq, Eq)]
 pub enum DeathCause {
@@ -8,23 +10,39 @@ pub enum DeathCause {
     Assassination,
     Starvation,
     Stab(UserId),
+    /// The player left the server or lost the player role, and the host gave up on them
+    Absence,
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub enum SubstitutionStatus {
     HasNot,
     CurrentlyIs,
     Has,
 }
 
-impl fmt::Display for DeathCause {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+impl DeathCause {
+    /// Describes how `player` died, in `locale`
+    pub fn describe(&self, theme: &Theme, locale: &str, player: UserId) -> String {
+        let key = match self {
+            DeathCause::Sorcery => "death.sorcery",
+            DeathCause::Beheading => "death.beheading",
+            DeathCause::Assassination => "death.assassination",
+            DeathCause::Starvation => "death.starvation",
+            DeathCause::Stab(_) => "death.stab",
+            DeathCause::Absence => "death.absence",
+        };
+
         match self {
-            DeathCause::Sorcery => write!(f, "was burnt to a crisp using sorcery."),
-            DeathCause::Beheading => write!(f, "was beheaded."),
-            DeathCause::Assassination => write!(f, "was assassinated."),
-            DeathCause::Starvation => write!(f, "became a mumy due to starvation."),
-            DeathCause::Stab(id) => write!(f, "was stabbed by {}", id.mention()),
+            DeathCause::Stab(stabber) => theme.tr_with(
+                locale,
+                key,
+                &[
+                    ("player", &player.mention()),
+                    ("stabber", &stabber.mention()),
+                ],
+            ),
+            _ => theme.tr_with(locale, key, &[("player", &player.mention())]),
         }
     }
 }
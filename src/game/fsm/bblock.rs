@@ -9,7 +9,7 @@ use serenity::{model::id::UserId, prelude::*};
 use std::collections::BTreeMap;
 use tracing::{info, instrument};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub(super) struct BBlock {
     players: BTreeMap<UserId, Player>,
     day: u8,
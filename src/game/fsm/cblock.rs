@@ -4,13 +4,15 @@
 //! * the secret meeting partners get chosen & the secret meetings happen
 //! * the King selects a target & either the Sorcerer or Knight will decide whether to kill the target or not
 
-use super::{macros::state::*, reactions::*, *};
+use super::{
+    decisions::{post_target_prompt, DecisionKind, MURDER_PROMPT, SECRET_MEETING_PROMPT},
+    macros::state::*,
+    *,
+};
 use crate::{
     game::{player::Player, roles::RoleName},
-    helpers::{
-        choose_target::build_embed_for_target_choice,
-        confirm_murder::build_embed_for_murder_confirmation, perms, react::react_with,
-    },
+    helpers::perms,
+    i18n,
 };
 
 use serenity::{
@@ -28,13 +30,13 @@ use std::{
 };
 use tracing::{info, instrument};
 
-mod tasks;
+pub(super) mod tasks;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub(super) struct CBlock {
     players: BTreeMap<UserId, Player>,
     day: u8,
-    king_murder_target: UserId,
+    king_murder_target: Option<UserId>,
     king_substitution_status: SubstitutionStatus,
 }
 
@@ -43,7 +45,7 @@ impl CBlock {
         Self {
             players,
             day,
-            king_murder_target: UserId::default(),
+            king_murder_target: None,
             king_substitution_status: kss,
         }
     }
@@ -79,10 +81,6 @@ impl GameMachine<CBlock> {
         if let Err(e) = self.make_king_select_target(ctx).await {
             info!("{}", e);
         }
-        info!("Making the king's assistant choose...");
-        if let Err(e) = self.make_assistant_choose(ctx).await {
-            info!("{}", e);
-        }
         info!("Going to the next block...");
 
         Next::Block(GameMachine {
@@ -96,10 +94,10 @@ impl GameMachine<CBlock> {
     }
 
     pub(super) fn set_king_murder_target(&mut self, target: UserId) {
-        self.state.king_murder_target = target;
+        self.state.king_murder_target = Some(target);
     }
 
-    pub(super) fn king_murder_target(&self) -> UserId {
+    pub(super) fn king_murder_target(&self) -> Option<UserId> {
         self.state.king_murder_target
     }
 
@@ -113,15 +111,6 @@ impl GameMachine<CBlock> {
             .collect::<Vec<_>>();
         info!("OK! Succesfully collected rooms");
 
-        info!("Trying to build an embed");
-        let embed = build_embed_for_target_choice(
-            ctx,
-            self.state.players(),
-            "Please select a partner for your secret meeting",
-        )
-        .await?;
-        info!("Embed built successfuly");
-
         for user_and_room in rooms {
             if !self
                 .state
@@ -134,25 +123,24 @@ impl GameMachine<CBlock> {
             }
 
             info!("Trying to send messages...");
-            let msg = user_and_room
-                .1
-                .send_message(ctx, |m| m.set_embed(embed.clone()))
-                .await?;
+            let msg = post_target_prompt(
+                ctx,
+                SECRET_MEETING_PROMPT,
+                user_and_room.0,
+                user_and_room.1,
+                self.metadata.guild,
+                self.state.players(),
+                &self.metadata.composites,
+            )
+            .await?;
             info!("We succeeded. Room={}", user_and_room.1.mention());
 
-            let mut emojis = vec![];
-            for (idx, player) in self.state.players().values().enumerate() {
-                if player.is_alive() {
-                    emojis.push(NUMBER_EMOJIS_ONE_TO_SIX[idx]);
-                }
-            }
-            react_with(ctx, &msg, &emojis).await?;
-
-            tokio::task::spawn(tasks::handle_secret_meeting_selection(
-                ctx.clone(),
+            self.metadata.wait_for_decision(
+                ctx,
                 msg,
-                user_and_room,
-            ));
+                DecisionKind::SecretMeetingPartner,
+                user_and_room.0,
+            );
         }
 
         Ok(())
@@ -173,9 +161,10 @@ impl GameMachine<CBlock> {
             res
         };
 
+        let locale = i18n::locale(ctx, Some(self.metadata.guild), None).await;
         let mut embed = CreateEmbed::default();
         embed
-            .title("Secret meeting partners")
+            .title(i18n::tr(&locale, "meeting.partners"))
             .field("A => B", partners, true);
 
         self.metadata
@@ -195,6 +184,7 @@ impl GameMachine<CBlock> {
                     .kind(ChannelType::Category)
             })
             .await?;
+        self.metadata.created.channels.push(meetings_category.id);
 
         let mut players_mapped_to_secret_rooms: HashMap<UserId, ChannelId> = Default::default();
 
@@ -248,6 +238,7 @@ impl GameMachine<CBlock> {
                 .await?;
 
             players_mapped_to_secret_rooms.insert(guest_id, channel.id);
+            self.metadata.created.channels.push(channel.id);
 
             channel.create_permission(ctx, &guest_perms).await?;
             channel.create_permission(ctx, &host_perms).await?;
@@ -303,103 +294,27 @@ impl GameMachine<CBlock> {
             None => return Err("There is a unusual lack of nobility".into()), // I *think* this shouldn't happen as no nobility => someone won, already
         };
 
+        let room = self.state.players().get(&king).unwrap().room();
         if !self.state.is_sorcerer_alive() || !self.state.is_knight_alive() {
-            self.state
-                .players()
-                .get(&king)
-                .unwrap()
-                .room()
-                .say(
-                    ctx,
-                    "You cannot ask the dead to commit murder for you. Maybe pick up that knife?",
-                )
+            let locale = i18n::locale(ctx, Some(self.metadata.guild), Some(king)).await;
+            room.say(ctx, i18n::tr(&locale, "prompt.no_assistant"))
                 .await?;
             return Ok(());
         }
 
-        let embed = build_embed_for_target_choice(
+        let msg = post_target_prompt(
             ctx,
-            self.state.players(),
-            "Please select a target for 「 Murder 」",
-        )
-        .await?;
-
-        let msg = self
-            .state
-            .players()
-            .get(&king)
-            .unwrap()
-            .room()
-            .send_message(ctx, |m| m.set_embed(embed))
-            .await?;
-
-        let mut emojis = vec![];
-        for (idx, player) in self.state.players().values().enumerate() {
-            if player.is_alive() {
-                emojis.push(NUMBER_EMOJIS_ONE_TO_SIX[idx]);
-            }
-        }
-
-        react_with(ctx, &msg, &emojis).await?;
-        let room_id = msg.channel_id;
-
-        tokio::task::spawn(tasks::handle_king_choosing_target(
-            ctx.clone(),
-            msg,
+            MURDER_PROMPT,
             king,
-            room_id,
-        ));
-
-        Ok(())
-    }
-
-    async fn make_assistant_choose(&mut self, ctx: &Context) -> CommandResult {
-        let embed = build_embed_for_murder_confirmation(
-            ctx,
-            self.state.king_murder_target,
+            room,
             self.metadata.guild,
+            self.state.players(),
+            &self.metadata.composites,
         )
         .await?;
 
-        let sorc_or_knight = {
-            let mut res = None;
-
-            for player in self.state.players().iter() {
-                if player.1.is_alive()
-                    && [RoleName::Knight, RoleName::Sorcerer].contains(&player.1.role_name())
-                {
-                    res = Some(player.0);
-                    break;
-                }
-            }
-
-            res
-        };
-
-        if sorc_or_knight.is_none() {
-            return Ok(());
-        }
-
-        let sorc_or_knight = sorc_or_knight.unwrap();
-
-        let msg = self
-            .state
-            .players()
-            .get(sorc_or_knight)
-            .unwrap()
-            .room()
-            .send_message(ctx, |m| m.set_embed(embed))
-            .await?;
-
-        react_with(ctx, &msg, &YES_NO_EMOJIS).await?;
-
-        let room_id = msg.channel_id;
-        tokio::task::spawn(tasks::handle_assistant_choice(
-            ctx.clone(),
-            msg,
-            *sorc_or_knight,
-            room_id,
-        ));
+        self.metadata
+            .wait_for_decision(ctx, msg, DecisionKind::MurderTarget, king);
 
         Ok(())
     }
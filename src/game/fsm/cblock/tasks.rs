@@ -1,53 +1,82 @@
 use serenity::{
     client::Context,
-    model::{
-        channel::Message,
-        id::{ChannelId, UserId},
-    },
+    framework::standard::CommandResult,
+    model::{channel::Message, id::UserId, misc::Mentionable},
 };
 use tracing::{error, warn};
 
-use crate::game::fsm::{macros::tasks::expect_game, reactions::*};
+use crate::{
+    game::{
+        fsm::{
+            decisions::{
+                announce_default, await_decision, post_murder_confirmation, random_target,
+                Cancelled, DecisionKind, PendingDecision,
+            },
+            macros::tasks::expect_game,
+            reactions::*,
+        },
+        roles::RoleName,
+        AssistantDefault, Game, GameRules, TargetDefault,
+    },
+    themes,
+};
 
 pub async fn handle_secret_meeting_selection(
     ctx: Context,
     msg: Message,
-    user_and_room: (UserId, ChannelId),
+    decision: PendingDecision,
+    rules: GameRules,
+    host: UserId,
 ) {
-    if let Some(reaction) = msg
-        .await_reaction(&ctx)
-        .author_id(user_and_room.0)
-        .channel_id(user_and_room.1)
-        .filter(|r| NUMBER_EMOJIS_ONE_TO_SIX.contains(&r.emoji.to_string().as_str()))
-        .await
-    {
-        let emoji = reaction.as_inner_ref().emoji.to_string();
-        if let Ok(idx) = NUMBER_EMOJIS_ONE_TO_SIX.binary_search(&emoji.as_str()) {
-            let game = expect_game!(ctx, "handle_secret_meeting_selection");
-            let mut game = game.write().await;
-
-            // Panic safety: The only GameState that's not a TimeBlock is NotStarted, and this can never wake up then
-            let id = game
-                .players()
-                .expect("handle_secret_meeting_selection should only be called by a TimeBlock")
-                .keys()
-                .nth(idx)
-                .copied();
-            match id {
-                Some(id) => {
-                    // Panic safety: The only GameState that's not a TimeBlock is NotStarted, and this can never wake up then
-                    game.players_mut()
-                        .expect(
-                            "handle_secret_meeting_selection should only be called by a TimeBlock",
-                        )
-                        .get_mut(&user_and_room.0)
-                        .unwrap()
-                        .set_secret_meeting_partner(id);
-                }
-                None => {
-                    error!("Got a wrong reaction somehow");
-                }
+    let user_and_room = (decision.player, decision.room);
+    let reaction = await_decision(&ctx, &msg, &decision, &NUMBER_EMOJIS_ONE_TO_SIX, &rules).await;
+    let reaction = match reaction {
+        Ok(reaction) => reaction,
+        Err(Cancelled) => return,
+    };
+
+    let game = expect_game!(ctx, "handle_secret_meeting_selection");
+    let mut game = game.write().await;
+    if !game.finish_decision(&decision) {
+        return;
+    }
+
+    let players = match game.players() {
+        Some(players) => players,
+        None => {
+            warn!("handle_secret_meeting_selection woke up outside of a time block");
+            return;
+        }
+    };
+
+    let id = match reaction {
+        Some(emoji) => match NUMBER_EMOJIS_ONE_TO_SIX.binary_search(&emoji.as_str()) {
+            Ok(idx) => players.keys().nth(idx).copied(),
+            Err(_) => None,
+        },
+        None => {
+            let partner = random_target(players, user_and_room.0);
+            if let Some(partner) = partner {
+                announce_default(
+                    &ctx,
+                    &decision,
+                    host,
+                    "default.meeting_partner",
+                    &[("partner", &partner.mention())],
+                )
+                .await;
             }
+            partner
+        }
+    };
+
+    match id {
+        Some(id) => match game.player_mut(user_and_room.0) {
+            Some(player) => player.set_secret_meeting_partner(id),
+            None => warn!("handle_secret_meeting_selection lost track of the player"),
+        },
+        None => {
+            error!("Got a wrong reaction somehow");
         }
     }
 }
@@ -55,77 +84,158 @@ pub async fn handle_secret_meeting_selection(
 pub async fn handle_king_choosing_target(
     ctx: Context,
     msg: Message,
-    king_id: UserId,
-    room_id: ChannelId,
+    decision: PendingDecision,
+    rules: GameRules,
+    host: UserId,
 ) {
-    if let Some(reaction) = msg
-        .await_reaction(&ctx)
-        .author_id(king_id)
-        .channel_id(room_id)
-        .filter(|r| NUMBER_EMOJIS_ONE_TO_SIX.contains(&r.emoji.to_string().as_str()))
-        .await
-    {
-        let emoji = reaction.as_inner_ref().emoji.to_string();
-        if let Ok(idx) = NUMBER_EMOJIS_ONE_TO_SIX.binary_search(&emoji.as_str()) {
-            let game = expect_game!(ctx, "handle_king_choosing_target");
-            let mut game = game.write().await;
-
-            // Panic safety: The only GameState that's not a TimeBlock is NotStarted, and this can never wake up then
-            let id = game
-                .players_mut()
-                .expect("handle_king_choosing_target should only be called in a TimeBlock")
-                .keys()
-                .nth(idx)
-                .copied();
-            match id {
-                Some(id) => {
-                    game.set_king_murder_target(id);
+    let king_id = decision.player;
+    let reaction = await_decision(&ctx, &msg, &decision, &NUMBER_EMOJIS_ONE_TO_SIX, &rules).await;
+    let reaction = match reaction {
+        Ok(reaction) => reaction,
+        Err(Cancelled) => return,
+    };
+
+    let game = expect_game!(ctx, "handle_king_choosing_target");
+    let mut game = game.write().await;
+    if !game.finish_decision(&decision) {
+        return;
+    }
+
+    let target = {
+        let players = match game.players() {
+            Some(players) => players,
+            None => {
+                warn!("handle_king_choosing_target woke up outside of a time block");
+                return;
+            }
+        };
+
+        let id = match reaction {
+            Some(emoji) => match NUMBER_EMOJIS_ONE_TO_SIX.binary_search(&emoji.as_str()) {
+                Ok(idx) => players.keys().nth(idx).copied(),
+                Err(_) => None,
+            },
+            None => {
+                let target = match rules.decision_defaults.murder_target {
+                    TargetDefault::Nobody => None,
+                    TargetDefault::Random => random_target(players, king_id),
+                };
+                match target {
+                    Some(target) => {
+                        announce_default(
+                            &ctx,
+                            &decision,
+                            host,
+                            "default.murder_target",
+                            &[("target", &target.mention())],
+                        )
+                        .await
+                    }
+                    None => {
+                        announce_default(&ctx, &decision, host, "default.murder_nobody", &[]).await
+                    }
                 }
-                None => {
-                    error!("Got a wrong reaction somehow");
+
+                if target.is_none() {
+                    return;
                 }
+                target
+            }
+        };
+
+        match id {
+            Some(id) => {
+                game.set_king_murder_target(id);
+                id
+            }
+            None => {
+                error!("Got a wrong reaction somehow");
+                return;
             }
         }
+    };
+
+    if let Err(e) = make_assistant_choose(&ctx, &mut game, target).await {
+        warn!("{}", e);
     }
 }
 
+/// Asks the Sorcerer or the Knight, whichever is alive, whether to carry out the 「 Murder 」
+async fn make_assistant_choose(ctx: &Context, game: &mut Game, target: UserId) -> CommandResult {
+    let assistant = game.players().and_then(|players| {
+        players
+            .iter()
+            .find(|(_, player)| {
+                player.is_alive()
+                    && [RoleName::Knight, RoleName::Sorcerer].contains(&player.role_name())
+            })
+            .map(|(id, player)| (*id, player.room()))
+    });
+
+    let (assistant_id, room_id) = match assistant {
+        Some(assistant) => assistant,
+        None => return Ok(()),
+    };
+
+    let msg = post_murder_confirmation(ctx, room_id, target, game.guild()).await?;
+    game.wait_for_decision(
+        ctx,
+        msg,
+        DecisionKind::MurderConfirmation { target },
+        assistant_id,
+    );
+
+    Ok(())
+}
+
 pub async fn handle_assistant_choice(
     ctx: Context,
     msg: Message,
-    assistant_id: UserId,
-    room_id: ChannelId,
+    decision: PendingDecision,
+    target_id: UserId,
+    rules: GameRules,
+    host: UserId,
 ) {
-    if let Some(reaction) = msg
-        .await_reaction(&ctx)
-        .filter(|r| YES_NO_EMOJIS.contains(&r.emoji.to_string().as_str()))
-        .author_id(assistant_id)
-        .channel_id(room_id)
-        .await
-    {
-        if reaction.as_inner_ref().emoji.unicode_eq(YES_NO_EMOJIS[0]) {
-            let game = expect_game!(ctx, "handle_assistant_choice");
-            let mut game = game.write().await;
-
-            let target_id = if let Some(id) = game.king_murder_target() {
-                id
-            } else {
-                warn!("handle_assistance_choice woke up in the wrong block");
-                return;
-            };
+    let reaction = await_decision(&ctx, &msg, &decision, &YES_NO_EMOJIS, &rules).await;
+    let reaction = match reaction {
+        Ok(reaction) => reaction,
+        Err(Cancelled) => return,
+    };
+
+    let game = expect_game!(ctx, "handle_assistant_choice");
+    let mut game = game.write().await;
+    if !game.finish_decision(&decision) {
+        return;
+    }
 
-            let meeting_room = game.meeting_room();
-            let target = if let Some(players) = game.players_mut() {
-                players.get_mut(&target_id).unwrap()
+    let accepted = match reaction {
+        Some(emoji) => emoji == YES_NO_EMOJIS[0],
+        None => {
+            let accepted = rules.decision_defaults.assistant == AssistantDefault::Accept;
+            let key = if accepted {
+                "default.murder_accepted"
             } else {
-                warn!("handle_assistance_choice woke up in the wrong block");
-                return;
+                "default.murder_refused"
             };
-            let _ = target
-                .set_dead(target.role_name().into(), &ctx, meeting_room)
-                .await
-                .map_err(|e| {
-                    warn!("{}", e);
-                });
+            announce_default(&ctx, &decision, host, key, &[]).await;
+            accepted
         }
+    };
+
+    if accepted {
+        let meeting_room = game.meeting_room();
+        let theme = themes::get(&ctx, game.theme()).await;
+        let target = if let Some(target) = game.player_mut(target_id) {
+            target
+        } else {
+            warn!("handle_assistance_choice woke up in the wrong block");
+            return;
+        };
+        let _ = target
+            .set_dead(target.role_name().into(), &ctx, meeting_room, &theme)
+            .await
+            .map_err(|e| {
+                warn!("{}", e);
+            });
     }
 }
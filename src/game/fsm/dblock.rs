@@ -8,7 +8,7 @@ use serenity::{model::id::UserId, prelude::*};
 use std::collections::BTreeMap;
 use tracing::{info, instrument};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub(super) struct DBlock {
     players: BTreeMap<UserId, Player>,
     day: u8,
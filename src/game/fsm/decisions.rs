@@ -0,0 +1,419 @@
+//! Waiting on the choices players make by reacting to a prompt in their room
+//!
+//! Every prompt has a deadline, set by the game's rules. Players get reminded of it a bit before it
+//! passes, and once it does the caller applies the rules' default outcome and lets the host know.
+//! Decisions are held while the game is paused, and their deadlines pushed out once it resumes.
+//!
+//! The decisions that are being waited on are kept on the game as [`PendingDecision`]s, so after a
+//! restart the bot can pick their prompts up again, see [`Game::resume_decisions`].
+//!
+//! [`Game::resume_decisions`]: super::Game::resume_decisions
+
+use super::{cblock::tasks, eblock, reactions::*, Player};
+use crate::{
+    commands::game::{persist_game, GameContainer},
+    game::GameRules,
+    helpers::{
+        choose_target::{build_embed_for_target_choice, CompositeCache, TargetChoice},
+        confirm_murder::build_embed_for_murder_confirmation,
+        react::react_with,
+    },
+    i18n, shutdown,
+};
+
+use chrono::{DateTime, Utc};
+use rand::seq::IteratorRandom;
+use serde::{Deserialize, Serialize};
+use serenity::{
+    framework::standard::CommandResult,
+    model::{
+        channel::Message,
+        id::{ChannelId, GuildId, MessageId, UserId},
+        misc::Mentionable,
+    },
+    prelude::*,
+};
+use std::{collections::BTreeMap, fmt::Display, time::Duration};
+use tracing::{warn, Instrument};
+
+/// The catalogue keys of the titles of the prompts for picking a target
+pub(super) static SECRET_MEETING_PROMPT: &str = "prompt.secret_meeting";
+pub(super) static MURDER_PROMPT: &str = "prompt.murder";
+pub(super) static ASSASSINATION_PROMPT: &str = "prompt.assassination";
+
+/// How often a decision that's held while the game is paused checks whether it resumed
+const PAUSE_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// A choice a player still has to make by reacting to a prompt in their room
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(super) struct PendingDecision {
+    pub kind: DecisionKind,
+    pub player: UserId,
+    pub room: ChannelId,
+    /// The prompt the player reacts to
+    pub message: MessageId,
+    /// When the rules' default outcome gets applied
+    pub deadline: DateTime<Utc>,
+}
+
+impl PendingDecision {
+    /// A decision `player` has to make by reacting to `msg`, starting now
+    pub fn new(kind: DecisionKind, player: UserId, msg: &Message, rules: &GameRules) -> Self {
+        let now = Utc::now();
+        let deadline = chrono::Duration::from_std(rules.decision_timeout())
+            .ok()
+            .and_then(|timeout| now.checked_add_signed(timeout))
+            .unwrap_or(chrono::MAX_DATETIME);
+
+        Self {
+            kind,
+            player,
+            room: msg.channel_id,
+            message: msg.id,
+            deadline,
+        }
+    }
+
+    /// Whether `other` is the same decision, even if its deadline was pushed out since
+    pub fn is(&self, other: &PendingDecision) -> bool {
+        self.kind == other.kind && self.player == other.player && self.message == other.message
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub(super) enum DecisionKind {
+    SecretMeetingPartner,
+    MurderTarget,
+    /// Whether the Sorcerer or the Knight carries out the 「 Murder 」 of `target`
+    MurderConfirmation {
+        target: UserId,
+    },
+    AssassinationTarget,
+}
+
+/// The bot started shutting down while a decision was being waited on
+///
+/// The game gets saved as it is, so whoever was waiting should stop without changing anything.
+pub(super) struct Cancelled;
+
+/// Waits on `decision` in the background, applying its outcome to the running game once it's made
+pub(super) fn spawn(
+    ctx: &Context,
+    msg: Message,
+    decision: PendingDecision,
+    rules: GameRules,
+    host: UserId,
+) {
+    let ctx = ctx.clone();
+    tokio::spawn(
+        async move {
+            match decision.kind {
+                DecisionKind::SecretMeetingPartner => {
+                    tasks::handle_secret_meeting_selection(ctx.clone(), msg, decision, rules, host)
+                        .await
+                }
+                DecisionKind::MurderTarget => {
+                    tasks::handle_king_choosing_target(ctx.clone(), msg, decision, rules, host)
+                        .await
+                }
+                DecisionKind::MurderConfirmation { target } => {
+                    tasks::handle_assistant_choice(ctx.clone(), msg, decision, target, rules, host)
+                        .await
+                }
+                DecisionKind::AssassinationTarget => {
+                    eblock::handle_assassination(ctx.clone(), msg, decision, rules, host).await
+                }
+            }
+
+            // The decision isn't pending anymore, which has to survive a restart too
+            persist_game(&ctx).await;
+        }
+        .in_current_span(),
+    );
+}
+
+/// Waits for the player to react to `msg` with one of `emojis`, returning the emoji they picked
+///
+/// Returns `None` if they didn't do so before the deadline.
+pub(super) async fn await_decision(
+    ctx: &Context,
+    msg: &Message,
+    decision: &PendingDecision,
+    emojis: &'static [&'static str],
+    rules: &GameRules,
+) -> Result<Option<String>, Cancelled> {
+    if let Some(emoji) = earlier_reaction(ctx, msg, decision.player, emojis).await {
+        return Ok(Some(emoji));
+    }
+
+    let reminder = rules.decision_reminder().min(rules.decision_timeout());
+    // A prompt that was picked up again after a restart may be past its reminder already
+    let mut reminded = reminder.as_secs() == 0 || time_until(decision.deadline) <= reminder;
+
+    loop {
+        let deadline = match current_deadline(ctx, decision).await {
+            Some(deadline) => deadline,
+            // Players can still choose while the game is paused, it just doesn't time out
+            None => match collect(ctx, msg, decision, emojis, PAUSE_CHECK_INTERVAL).await? {
+                Some(emoji) => return Ok(Some(emoji)),
+                None => continue,
+            },
+        };
+        let left = time_until(deadline);
+
+        match left.checked_sub(reminder).filter(|_| !reminded) {
+            Some(until_reminder) => {
+                let reaction = collect(ctx, msg, decision, emojis, until_reminder).await?;
+                if reaction.is_some() {
+                    return Ok(reaction);
+                }
+                // The game may have been paused while waiting, the reminder waits for it then
+                if current_deadline(ctx, decision).await != Some(deadline) {
+                    continue;
+                }
+
+                remind(ctx, decision, rules).await;
+                reminded = true;
+            }
+            None => {
+                let reaction = collect(ctx, msg, decision, emojis, left).await?;
+                if reaction.is_some() {
+                    return Ok(reaction);
+                }
+                match current_deadline(ctx, decision).await {
+                    Some(deadline) if deadline <= Utc::now() => return Ok(None),
+                    _ => continue,
+                }
+            }
+        }
+    }
+}
+
+async fn remind(ctx: &Context, decision: &PendingDecision, rules: &GameRules) {
+    let minutes = rules
+        .decision_reminder_minutes
+        .min(rules.decision_timeout_minutes);
+    let locale = locale_in(ctx, decision.room, decision.player).await;
+    let _ = decision
+        .room
+        .say(
+            ctx,
+            i18n::tr_with(
+                &locale,
+                "decision.reminder",
+                &[
+                    ("player", &decision.player.mention()),
+                    ("minutes", &minutes),
+                ],
+            ),
+        )
+        .await
+        .map_err(|e| warn!("Failed to send a decision reminder: {}", e));
+}
+
+/// When `decision` times out, or `None` while the game is paused
+async fn current_deadline(ctx: &Context, decision: &PendingDecision) -> Option<DateTime<Utc>> {
+    let game = ctx.data.read().await.get::<GameContainer>().cloned();
+    match game {
+        Some(game) => game.read().await.decision_deadline(decision),
+        None => Some(decision.deadline),
+    }
+}
+
+async fn collect(
+    ctx: &Context,
+    msg: &Message,
+    decision: &PendingDecision,
+    emojis: &'static [&'static str],
+    timeout: Duration,
+) -> Result<Option<String>, Cancelled> {
+    let shutdown = shutdown::get(ctx).await;
+    let reaction = msg
+        .await_reaction(ctx)
+        .author_id(decision.player)
+        .channel_id(decision.room)
+        .filter(move |r| emojis.contains(&r.emoji.to_string().as_str()))
+        .timeout(timeout);
+
+    tokio::select! {
+        reaction = reaction => Ok(reaction.map(|r| r.as_inner_ref().emoji.to_string())),
+        _ = shutdown.wait() => Err(Cancelled),
+    }
+}
+
+/// Finds one of `emojis` that `player` reacted with before the reactions were being collected,
+/// like while the bot was restarting
+async fn earlier_reaction(
+    ctx: &Context,
+    msg: &Message,
+    player: UserId,
+    emojis: &[&str],
+) -> Option<String> {
+    for reaction in &msg.reactions {
+        let emoji = reaction.reaction_type.to_string();
+        // Freshly sent prompts don't have any reactions, and the bot's own don't count
+        if !emojis.contains(&emoji.as_str()) || reaction.count <= u64::from(reaction.me) {
+            continue;
+        }
+
+        match msg
+            .reaction_users(ctx, reaction.reaction_type.clone(), None, None::<UserId>)
+            .await
+        {
+            Ok(users) if users.iter().any(|user| user.id == player) => return Some(emoji),
+            Ok(_) => {}
+            Err(e) => warn!("Failed to look at who reacted to a prompt: {}", e),
+        }
+    }
+
+    None
+}
+
+fn time_until(deadline: DateTime<Utc>) -> Duration {
+    (deadline - Utc::now())
+        .to_std()
+        .unwrap_or_else(|_| Duration::from_secs(0))
+}
+
+/// Asks `player` in `room` to pick one of the alive `players`, titling the prompt with the
+/// catalogue entry `key` in their language
+pub(super) async fn post_target_prompt(
+    ctx: &Context,
+    key: &str,
+    player: UserId,
+    room: ChannelId,
+    guild: GuildId,
+    players: &BTreeMap<UserId, Player>,
+    composites: &CompositeCache,
+) -> CommandResult<Message> {
+    let locale = i18n::locale(ctx, Some(guild), Some(player)).await;
+    let title = i18n::tr(&locale, key);
+    let choice = build_embed_for_target_choice(ctx, players, &title, composites).await?;
+    post_target_choice(ctx, room, &choice, players).await
+}
+
+/// Sends `choice` to `room`, with a reaction to pick every alive player with
+async fn post_target_choice(
+    ctx: &Context,
+    room: ChannelId,
+    choice: &TargetChoice,
+    players: &BTreeMap<UserId, Player>,
+) -> CommandResult<Message> {
+    let msg = room
+        .send_message(ctx, |m| {
+            m.set_embed(choice.embed.clone())
+                .add_file(choice.attachment())
+        })
+        .await?;
+
+    let mut emojis = vec![];
+    for (idx, player) in players.values().enumerate() {
+        if player.is_alive() {
+            emojis.push(NUMBER_EMOJIS_ONE_TO_SIX[idx]);
+        }
+    }
+    react_with(ctx, &msg, &emojis).await?;
+
+    Ok(msg)
+}
+
+/// Asks in `room` whether to carry out the 「 Murder 」 of `target`
+pub(super) async fn post_murder_confirmation(
+    ctx: &Context,
+    room: ChannelId,
+    target: UserId,
+    guild: GuildId,
+) -> CommandResult<Message> {
+    let embed = build_embed_for_murder_confirmation(ctx, target, guild).await?;
+    let msg = room.send_message(ctx, |m| m.set_embed(embed)).await?;
+
+    react_with(ctx, &msg, &YES_NO_EMOJIS).await?;
+
+    Ok(msg)
+}
+
+/// Posts the prompt of `decision` again, for when the one it was made with is gone
+pub(super) async fn repost(
+    ctx: &Context,
+    decision: &PendingDecision,
+    players: &BTreeMap<UserId, Player>,
+    guild: GuildId,
+    composites: &CompositeCache,
+) -> CommandResult<Message> {
+    let key = match decision.kind {
+        DecisionKind::SecretMeetingPartner => SECRET_MEETING_PROMPT,
+        DecisionKind::MurderTarget => MURDER_PROMPT,
+        DecisionKind::AssassinationTarget => ASSASSINATION_PROMPT,
+        DecisionKind::MurderConfirmation { target } => {
+            return post_murder_confirmation(ctx, decision.room, target, guild).await
+        }
+    };
+
+    post_target_prompt(
+        ctx,
+        key,
+        decision.player,
+        decision.room,
+        guild,
+        players,
+        composites,
+    )
+    .await
+}
+
+/// Picks a random alive player that isn't `chooser`
+pub(super) fn random_target(players: &BTreeMap<UserId, Player>, chooser: UserId) -> Option<UserId> {
+    players
+        .iter()
+        .filter(|(id, player)| **id != chooser && player.is_alive())
+        .map(|(id, _)| *id)
+        .choose(&mut rand::thread_rng())
+}
+
+/// Tells the player in their room, and the host in their DMs, that a default outcome was applied
+///
+/// The notices are `<key>.player` and `<key>.host` in the catalogue, with `{player}` and `args`
+/// filled in.
+pub(super) async fn announce_default(
+    ctx: &Context,
+    decision: &PendingDecision,
+    host: UserId,
+    key: &str,
+    args: &[(&str, &dyn Display)],
+) {
+    let player = decision.player.mention();
+    let mut args = args.to_vec();
+    args.push(("player", &player));
+
+    let locale = locale_in(ctx, decision.room, decision.player).await;
+    let _ = decision
+        .room
+        .say(
+            ctx,
+            i18n::tr_with(&locale, &format!("{}.player", key), &args),
+        )
+        .await
+        .map_err(|e| warn!("Failed to tell a player about a default outcome: {}", e));
+
+    // Nobody needs to hear about it in games the bot hosts itself
+    if host == ctx.cache.current_user_id().await {
+        return;
+    }
+
+    let locale = locale_in(ctx, decision.room, host).await;
+    match host.create_dm_channel(ctx).await {
+        Ok(dm) => {
+            let _ = dm
+                .say(ctx, i18n::tr_with(&locale, &format!("{}.host", key), &args))
+                .await
+                .map_err(|e| warn!("Failed to tell the host about a default outcome: {}", e));
+        }
+        Err(e) => warn!("Failed to open a DM with the host: {}", e),
+    }
+}
+
+/// The locale to talk to `user` in, about the game `room` is part of
+async fn locale_in(ctx: &Context, room: ChannelId, user: UserId) -> String {
+    let guild = ctx.cache.guild_channel(room).await.map(|c| c.guild_id);
+    i18n::locale(ctx, guild, Some(user)).await
+}
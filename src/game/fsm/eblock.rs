@@ -6,20 +6,24 @@
 //!  * the Revolutionary assassinates
 
 use super::{
+    decisions::{
+        announce_default, await_decision, post_target_prompt, random_target, Cancelled,
+        DecisionKind, PendingDecision, ASSASSINATION_PROMPT,
+    },
     macros::{state::*, tasks::expect_game},
     reactions::*,
     *,
 };
 use crate::{
-    game::{item, DeathCause},
-    helpers::{choose_target::build_embed_for_target_choice, react::react_with},
+    game::{item, DeathCause, GameRules, TargetDefault},
+    themes,
 };
 
 use serenity::{framework::standard::CommandResult, model::id::UserId, prelude::*};
 use std::collections::BTreeMap;
 use tracing::{info, instrument};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub(super) struct EBlock {
     players: BTreeMap<UserId, Player>,
     day: u8,
@@ -79,6 +83,7 @@ impl GameMachine<EBlock> {
     }
 
     async fn make_players_eat_or_starve(&mut self, ctx: &Context) -> CommandResult {
+        let theme = themes::get(ctx, &self.metadata.theme).await;
         for player in self.state.players_mut().iter_mut() {
             let items = player.1.items_mut();
 
@@ -89,7 +94,12 @@ impl GameMachine<EBlock> {
             } else {
                 player
                     .1
-                    .set_dead(DeathCause::Starvation, ctx, self.metadata.meeting_room)
+                    .set_dead(
+                        DeathCause::Starvation,
+                        ctx,
+                        self.metadata.meeting_room,
+                        &theme,
+                    )
                     .await?;
             }
         }
@@ -116,33 +126,21 @@ impl GameMachine<EBlock> {
             }
         };
 
-        let embed = build_embed_for_target_choice(
+        let (revolutionary, room) = (*revolutionary.0, revolutionary.1.room());
+
+        let msg = post_target_prompt(
             ctx,
+            ASSASSINATION_PROMPT,
+            revolutionary,
+            room,
+            self.metadata.guild,
             self.state.players(),
-            "Please select a target for 「 Murder 」",
+            &self.metadata.composites,
         )
         .await?;
 
-        let msg = revolutionary
-            .1
-            .room()
-            .send_message(ctx, |m| m.set_embed(embed))
-            .await?;
-
-        let mut emojis = vec![];
-        for (idx, player) in self.state.players().values().enumerate() {
-            if player.is_alive() {
-                emojis.push(NUMBER_EMOJIS_ONE_TO_SIX[idx]);
-            }
-        }
-        react_with(ctx, &msg, &emojis).await?;
-
-        tokio::task::spawn(handle_assassination(
-            ctx.clone(),
-            msg,
-            *revolutionary.0,
-            revolutionary.1.room(),
-        ));
+        self.metadata
+            .wait_for_decision(ctx, msg, DecisionKind::AssassinationTarget, revolutionary);
 
         Ok(())
     }
@@ -150,71 +148,157 @@ impl GameMachine<EBlock> {
     impl_common_state_boilerplate!();
 }
 
-async fn handle_assassination(
+pub(super) async fn handle_assassination(
     ctx: Context,
     msg: Message,
-    revolutionary_id: UserId,
-    room_id: ChannelId,
+    decision: PendingDecision,
+    rules: GameRules,
+    host: UserId,
 ) {
-    if let Some(reaction) = msg
-        .await_reaction(&ctx)
-        .author_id(revolutionary_id)
-        .channel_id(room_id)
-        .filter(|r| NUMBER_EMOJIS_ONE_TO_SIX.contains(&r.emoji.to_string().as_str()))
-        .await
-    {
-        static EXPECT_ERR_MESSAGE: &str = "handle_assassination called outside of the E Block";
-        let game = expect_game!(ctx, "handle_assassination");
-        let mut game = game.write().await;
-
-        let meeting_room = game.meeting_room();
-
-        let emoji = reaction.as_inner_ref().emoji.to_string();
-        if let Ok(idx) = NUMBER_EMOJIS_ONE_TO_SIX.binary_search(&emoji.as_str()) {
-            let id = game
-                .players()
-                .expect(EXPECT_ERR_MESSAGE)
-                .keys()
-                .nth(idx)
-                .copied();
-            match id {
-                Some(id) => {
-                    let hit_king = game.player(id).expect("handle_assassination: binary_search returned an index the array I think").role_name() == RoleName::King;
-                    if hit_king {
-                        let king_has_substituted =
-                            game.king_has_substituted().expect(EXPECT_ERR_MESSAGE);
-
-                        if king_has_substituted {
-                            let double = {
-                                let mut res = None;
-                                for player in game.players_mut().expect(EXPECT_ERR_MESSAGE) {
-                                    if player.1.role_name() == RoleName::TheDouble {
-                                        res = Some(player);
-                                        break;
-                                    }
-                                }
-                                res
-                            };
-
-                            let _ =double
-                                .expect("Should have a player here, i.e. the king shouldn't be allowed to substitute when the double is dead")
-                                .1
-                                .set_dead(DeathCause::Assassination, &ctx, meeting_room)
-                                .await.map_err(|e| warn!("{}", e));
-                        } else {
-                            let player = game.player_mut(id);
-                            let _ = player
-                                .expect("Should have a player here")
-                                .set_dead(DeathCause::Assassination, &ctx, meeting_room)
-                                .await
-                                .map_err(|e| warn!("{}", e));
-                        }
-                    }
-                }
+    let revolutionary_id = decision.player;
+    let reaction = await_decision(&ctx, &msg, &decision, &NUMBER_EMOJIS_ONE_TO_SIX, &rules).await;
+    let reaction = match reaction {
+        Ok(reaction) => reaction,
+        Err(Cancelled) => return,
+    };
+
+    let game = expect_game!(ctx, "handle_assassination");
+    let mut game = game.write().await;
+    if !game.finish_decision(&decision) {
+        return;
+    }
+
+    let meeting_room = game.meeting_room();
+    let theme = themes::get(&ctx, game.theme()).await;
+    let players = match game.players() {
+        Some(players) => players,
+        None => {
+            warn!("handle_assassination woke up outside of a time block");
+            return;
+        }
+    };
+
+    let id = match reaction {
+        Some(emoji) => {
+            match NUMBER_EMOJIS_ONE_TO_SIX
+                .binary_search(&emoji.as_str())
+                .ok()
+                .and_then(|idx| players.keys().nth(idx).copied())
+            {
+                Some(id) => id,
                 None => {
                     error!("Got a wrong reaction somehow");
+                    return;
+                }
+            }
+        }
+        None => {
+            let target = match rules.decision_defaults.assassination_target {
+                TargetDefault::Nobody => None,
+                TargetDefault::Random => random_target(players, revolutionary_id),
+            };
+            match target {
+                Some(target) => {
+                    announce_default(
+                        &ctx,
+                        &decision,
+                        host,
+                        "default.assassination_target",
+                        &[("target", &target.mention())],
+                    )
+                    .await
+                }
+                None => {
+                    announce_default(&ctx, &decision, host, "default.assassination_nobody", &[])
+                        .await
                 }
             }
+
+            match target {
+                Some(target) => target,
+                None => return,
+            }
         }
+    };
+
+    let king_has_substituted = game.king_has_substituted().unwrap_or(false);
+    let victim = match assassination_victim(players, id, king_has_substituted) {
+        Some(victim) => victim,
+        None => return,
+    };
+    let victim = match game.player_mut(victim) {
+        Some(victim) => victim,
+        None => return,
+    };
+
+    let _ = victim
+        .set_dead(DeathCause::Assassination, &ctx, meeting_room, &theme)
+        .await
+        .map_err(|e| warn!("{}", e));
+}
+
+/// Who dies when `target` is picked for 「 Assassination 」, if anyone
+///
+/// Whoever the Revolutionary picks dies, except that the Double dies in the King's place if they
+/// 「 Substitute 」d.
+fn assassination_victim(
+    players: &BTreeMap<UserId, Player>,
+    target: UserId,
+    king_has_substituted: bool,
+) -> Option<UserId> {
+    if players.get(&target)?.role_name() == RoleName::King && king_has_substituted {
+        players
+            .values()
+            .find(|player| player.role_name() == RoleName::TheDouble)
+            .map(Player::id)
+    } else {
+        Some(target)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::roles::RoleHolder;
+
+    /// A player with every role, the King being 1, the Knight 2 and the Double 6
+    fn players() -> BTreeMap<UserId, Player> {
+        RoleHolder::all()
+            .into_iter()
+            .enumerate()
+            .map(|(idx, role)| {
+                let id = UserId(idx as u64 + 1);
+                let room = ChannelId(idx as u64 + 1);
+                let player = Player::new(id, role, room, String::new(), &GameRules::default());
+                (id, player)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn assassinates_whoever_was_picked() {
+        let players = players();
+
+        assert_eq!(
+            assassination_victim(&players, UserId(2), false),
+            Some(UserId(2))
+        );
+        assert_eq!(
+            assassination_victim(&players, UserId(2), true),
+            Some(UserId(2))
+        );
+        assert_eq!(
+            assassination_victim(&players, UserId(1), false),
+            Some(UserId(1))
+        );
+        assert_eq!(assassination_victim(&players, UserId(7), false), None);
+    }
+
+    #[test]
+    fn the_double_dies_for_a_substituted_king() {
+        assert_eq!(
+            assassination_victim(&players(), UserId(1), true),
+            Some(UserId(6))
+        );
     }
 }
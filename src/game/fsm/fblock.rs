@@ -8,7 +8,7 @@ use super::{macros::state::*, *};
 use serenity::model::id::UserId;
 use std::collections::BTreeMap;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub(super) struct FBlock {
     players: BTreeMap<UserId, Player>,
     day: u8,
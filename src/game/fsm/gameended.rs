@@ -5,7 +5,7 @@
 
 use super::{macros::state::*, *};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub(super) struct GameEnded {
     players: BTreeMap<UserId, Player>,
     day: u8,
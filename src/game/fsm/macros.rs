@@ -65,7 +65,10 @@ pub mod state {
 
 #[macro_export]
 macro_rules! expect_game {
-    ($ctx:ident, $func: literal) => {{
+    ($ctx:ident, $func: literal) => {
+        $crate::expect_game!($ctx, $func, ())
+    };
+    ($ctx:ident, $func: literal, $ret:expr) => {{
         let game = $ctx
             .data
             .read()
@@ -80,7 +83,7 @@ macro_rules! expect_game {
                 "{} woke up but no game is running. (Game likely ended)",
                 $func
             );
-            return;
+            return $ret;
         }
     }};
 }
@@ -21,16 +21,27 @@ use dblock::*;
 use eblock::*;
 use fblock::*;
 use gameended::*;
-use tracing::{info, warn};
+pub use notstarted::{JoinError, LeaveError};
+use tracing::{field, info, info_span, warn, Instrument, Span};
 
 #[macro_use]
 mod macros;
+mod decisions;
+
+use decisions::{DecisionKind, PendingDecision};
 
 use super::roles::RoleName;
-use crate::game::data::*;
 pub use crate::game::player::Player;
-use crate::helpers::perms;
-
+use crate::game::{data::*, CreatedObjects, GameRules};
+use crate::{
+    helpers::{choose_target::CompositeCache, perms, status_board},
+    i18n, metrics,
+    storage::GuildConfig,
+    themes,
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use serenity::framework::standard::CommandResult;
 use serenity::model::id::UserId;
 use serenity::model::prelude::*;
@@ -39,40 +50,144 @@ use std::collections::BTreeMap;
 use tracing::error;
 
 /// Struct for the public API of the state machine
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Game(Wrapper);
 
 impl Game {
+    /// Creates a new game, taking everything that wasn't passed in explicitly from `config`
+    ///
+    /// `id` should be unique across games, the ID of the message that created the game works
     pub fn new(
+        id: u64,
         guild: GuildId,
         host: UserId,
         meeting_room: ChannelId,
         announcement_channel: ChannelId,
         player_role: RoleId,
-        delete_rooms_category_on_game_end: bool,
+        config: GuildConfig,
     ) -> Self {
         Self(Wrapper::NotStarted(GameMachine {
             metadata: Metadata {
+                id,
                 guild,
                 host,
                 meeting_room,
                 announcement_channel,
                 player_role,
-                delete_rooms_category_on_game_end,
+                delete_rooms_category_on_game_end: config.delete_rooms_on_end,
+                status_board: config.status_board,
+                rooms_category: config.rooms_category,
+                rooms_category_name: config.rooms_category_name,
+                rules: config.rules,
+                theme: themes::DEFAULT_THEME.to_string(),
+                created: CreatedObjects::default(),
+                pending_decisions: vec![],
+                autopilot: None,
+                schedule: None,
+                lobby_message: None,
+                paused_since: None,
+                composites: CompositeCache::default(),
             },
             state: NotStarted {
                 joined_users: vec![],
+                waitlist: vec![],
             },
         }))
     }
 
-    pub async fn transition_to_next_state(self, ctx: &Context) -> Self {
-        Game(self.0.next(ctx).await)
+    pub async fn transition_to_next_state(mut self, ctx: &Context) -> Self {
+        let from = self.state_name();
+        let span = self.span();
+        // Choices belong to the block they were asked in, whoever still waits on one gives up
+        self.drop_pending_decisions();
+        let game = Game(self.0.next(ctx).instrument(span).await);
+
+        metrics::block_transition(game.state_name());
+        async {
+            info!(
+                from,
+                to = game.state_name(),
+                "Moved on to the next time block"
+            );
+            if let Err(err) = game.announce_block(ctx).await {
+                warn!("Couldn't announce the new time block: {}", err);
+            }
+        }
+        .instrument(game.span())
+        .await;
+
+        game
+    }
+
+    /// A span for everything that happens in this game, so its whole history can be found in the
+    /// logs by its ID
+    ///
+    /// The span holds the day and block the game is in when it's made, days being counted from 1
+    /// like players see them.
+    pub fn span(&self) -> Span {
+        let span = info_span!(
+            "game",
+            guild = %self.guild(),
+            game_id = self.id(),
+            day = field::Empty,
+            block = self.state_name()
+        );
+        if let Some(day) = self.day() {
+            span.record("day", &(day + 1));
+        }
+        span
+    }
+
+    /// Tells everyone in the announcement channel which time block the game is in now
+    async fn announce_block(&self, ctx: &Context) -> CommandResult {
+        let key = match self.0 {
+            Wrapper::ABlock(_) => "block.a",
+            Wrapper::BBlock(_) => "block.b",
+            Wrapper::CBlock(_) => "block.c",
+            Wrapper::DBlock(_) => "block.d",
+            Wrapper::EBlock(_) => "block.e",
+            Wrapper::FBlock(_) => "block.f",
+            Wrapper::NotStarted(_) | Wrapper::GameEnded(_) => return Ok(()),
+        };
+
+        let locale = i18n::locale(ctx, Some(self.guild()), None).await;
+        let theme = themes::get(ctx, self.theme()).await;
+        // Days are counted from 0 internally
+        let day = self.day().unwrap_or_default() + 1;
+
+        let board = if self.status_board_enabled() {
+            status_board::build(ctx, self).await.unwrap_or_else(|err| {
+                warn!("Couldn't render the status board: {}", err);
+                None
+            })
+        } else {
+            None
+        };
+
+        self.announcement_channel()
+            .send_message(ctx, |m| {
+                m.content(theme.tr_with(&locale, key, &[("day", &day)]));
+                if let Some(board) = &board {
+                    m.add_file(board.attachment());
+                }
+                m
+            })
+            .await?;
+
+        Ok(())
     }
 
     pub async fn start(self, ctx: &Context) -> CommandResult<Self> {
+        let span = self.span();
         match self.0 {
-            Wrapper::NotStarted(ns) => Ok(Self(ns.next(ctx).await?.wrap())),
+            Wrapper::NotStarted(ns) => {
+                let mut game = Self(ns.next(ctx).instrument(span).await?.wrap());
+                // Hosts may start scheduled games early
+                game.unschedule();
+                info!(parent: &game.span(), "The game started");
+                metrics::block_transition(game.state_name());
+                Ok(game)
+            }
             other => {
                 error!("Game::start called on an already started game...");
                 Ok(Self(other))
@@ -81,14 +196,15 @@ impl Game {
     }
 
     pub async fn end(&mut self, ctx: &Context) -> CommandResult {
+        self.drop_pending_decisions();
         if let Some(players) = self.players() {
             let mut rooms_category = None;
             for player in players.iter() {
-                self.guild()
-                    .member(ctx, player.0)
-                    .await?
-                    .remove_role(ctx, self.metadata().player_role)
-                    .await?;
+                match self.guild().member(ctx, player.0).await {
+                    Ok(mut member) => member.remove_role(ctx, self.metadata().player_role).await?,
+                    // Players that left the server took the role with them
+                    Err(err) => info!("A player isn't in the server anymore: {}", err),
+                }
 
                 if self.metadata().delete_rooms_category_on_game_end {
                     info!("Deleting a room...");
@@ -101,7 +217,11 @@ impl Game {
                 }
             }
 
-            if self.metadata().delete_rooms_category_on_game_end && rooms_category.is_some() {
+            // A category that was set up beforehand is meant to be reused by the next games
+            if self.metadata().delete_rooms_category_on_game_end
+                && self.metadata().rooms_category.is_none()
+                && rooms_category.is_some()
+            {
                 info!("Deleting the category...");
                 rooms_category.unwrap().delete(ctx).await?;
                 info!("Deleted the category.")
@@ -111,6 +231,242 @@ impl Game {
         Ok(())
     }
 
+    /// Finds the prompts of the decisions that were pending when the bot stopped, posting them
+    /// anew if they were deleted, and returns them for [`Game::wait_for_resumed_decisions`]
+    ///
+    /// Decisions whose prompt can't be posted again are dropped.
+    pub async fn resume_decisions(&mut self, ctx: &Context) -> Vec<Message> {
+        if self.is_ended() {
+            return vec![];
+        }
+
+        let pending = std::mem::take(&mut self.0.metadata_mut().pending_decisions);
+        let mut prompts = Vec::with_capacity(pending.len());
+        for mut decision in pending {
+            let msg = match decision.room.message(ctx, decision.message).await {
+                Ok(msg) => msg,
+                Err(err) => {
+                    info!(
+                        "Couldn't find the prompt of a pending decision, posting it again: {}",
+                        err
+                    );
+                    let players = match self.players() {
+                        Some(players) => players,
+                        None => continue,
+                    };
+                    let reposted = decisions::repost(
+                        ctx,
+                        &decision,
+                        players,
+                        self.guild(),
+                        &self.metadata().composites,
+                    )
+                    .await;
+                    match reposted {
+                        Ok(msg) => msg,
+                        Err(err) => {
+                            warn!(
+                                "Couldn't post the prompt of a pending decision again, dropping it: {}",
+                                err
+                            );
+                            continue;
+                        }
+                    }
+                }
+            };
+
+            decision.message = msg.id;
+            self.0.metadata_mut().pending_decisions.push(decision);
+            prompts.push(msg);
+        }
+
+        prompts
+    }
+
+    /// Waits on the decisions [`Game::resume_decisions`] found the `prompts` of again
+    ///
+    /// Players who reacted while the bot was away have their choice applied right away, so the
+    /// game should be the running one by the time anyone looks at it.
+    pub fn wait_for_resumed_decisions(&self, ctx: &Context, prompts: Vec<Message>) {
+        let pending = &self.metadata().pending_decisions;
+        for msg in prompts {
+            match pending.iter().find(|decision| decision.message == msg.id) {
+                Some(decision) => decisions::spawn(
+                    ctx,
+                    msg,
+                    decision.clone(),
+                    self.rules().clone(),
+                    self.host(),
+                ),
+                None => warn!("Lost track of the decision prompted by {}", msg.id),
+            }
+        }
+    }
+
+    /// Makes `player` decide by reacting to `msg`, see [`Metadata::wait_for_decision`]
+    fn wait_for_decision(
+        &mut self,
+        ctx: &Context,
+        msg: Message,
+        kind: DecisionKind,
+        player: UserId,
+    ) {
+        self.0
+            .metadata_mut()
+            .wait_for_decision(ctx, msg, kind, player);
+    }
+
+    /// Forgets about `decision` as it was made, returning whether it was still being waited on
+    ///
+    /// Decisions that were handed over to a replacement player aren't anymore, so whoever waited
+    /// on them should leave the game alone.
+    fn finish_decision(&mut self, decision: &PendingDecision) -> bool {
+        let pending = &mut self.0.metadata_mut().pending_decisions;
+        let count = pending.len();
+        pending.retain(|pending| !pending.is(decision));
+        pending.len() != count
+    }
+
+    /// Gives the seat of the player `old` to `new`, who takes over their role, items, rooms and
+    /// the decisions they had yet to make
+    pub async fn replace_player(
+        &mut self,
+        ctx: &Context,
+        old: UserId,
+        new: UserId,
+    ) -> CommandResult {
+        let players = self
+            .players()
+            .ok_or("Players can only be replaced in a game that's running")?;
+        if players.contains_key(&new) {
+            return Err("The replacement is already playing in this game".into());
+        }
+        if !players.contains_key(&old) {
+            return Err("The player you want to replace isn't playing in this game".into());
+        }
+
+        // Nothing changes until the replacement has the player role, so the game is left as it
+        // was if they can't get it
+        let guild = self.guild();
+        let player_role = self.player_role();
+        guild
+            .member(ctx, new)
+            .await?
+            .add_role(ctx, player_role)
+            .await?;
+
+        let players = self
+            .players_mut()
+            .expect("replace_player checked that the game is running");
+        let mut player = players
+            .remove(&old)
+            .expect("replace_player checked that the player is playing");
+        player.replace_user(old, new);
+        player.set_absent(false);
+        for other in players.values_mut() {
+            other.replace_user(old, new);
+        }
+        players.insert(new, player);
+
+        if self.king_murder_target() == Some(old) {
+            self.set_king_murder_target(new);
+        }
+        for member in &mut self.0.metadata_mut().created.members_with_role {
+            if *member == old {
+                *member = new;
+            }
+        }
+
+        // The player may have left the server already
+        match guild.member(ctx, old).await {
+            Ok(mut member) => {
+                if let Err(err) = member.remove_role(ctx, player_role).await {
+                    warn!(
+                        "Couldn't take the player role from the replaced player: {}",
+                        err
+                    );
+                }
+            }
+            Err(err) => info!("The replaced player isn't in the server anymore: {}", err),
+        }
+
+        let players = self
+            .players()
+            .expect("replace_player checked that the game is running");
+        let mut channels = vec![players[&new].room(), self.meeting_room()];
+        channels.extend(players[&new].secret_meeting_rooms().map(|(_, room)| room));
+        channels.extend(
+            players
+                .values()
+                .flat_map(|player| player.secret_meeting_rooms())
+                .filter(|(host, _)| *host == new)
+                .map(|(_, room)| room),
+        );
+        channels.sort();
+        channels.dedup();
+        for channel in channels {
+            if let Err(err) = perms::move_member_overwrite(ctx, channel, old, new).await {
+                warn!(
+                    "Couldn't hand the permissions in {} over to the replacement: {}",
+                    channel, err
+                );
+            }
+        }
+
+        self.hand_over_decisions(ctx, old, new).await;
+        self.resume_if_everyone_is_back();
+        Ok(())
+    }
+
+    /// Posts the prompts of the pending decisions again after `old` was replaced by `new`, as the
+    /// players to choose from changed
+    async fn hand_over_decisions(&mut self, ctx: &Context, old: UserId, new: UserId) {
+        let pending = std::mem::take(&mut self.0.metadata_mut().pending_decisions);
+        for mut decision in pending {
+            if decision.player == old {
+                decision.player = new;
+            }
+            if let DecisionKind::MurderConfirmation { target } = &mut decision.kind {
+                if *target == old {
+                    *target = new;
+                }
+            }
+
+            let players = match self.players() {
+                Some(players) => players,
+                None => continue,
+            };
+            let reposted = decisions::repost(
+                ctx,
+                &decision,
+                players,
+                self.guild(),
+                &self.metadata().composites,
+            )
+            .await;
+            let msg = match reposted {
+                Ok(msg) => msg,
+                Err(err) => {
+                    warn!(
+                        "Couldn't post the prompt of a pending decision again, dropping it: {}",
+                        err
+                    );
+                    continue;
+                }
+            };
+
+            if let Err(err) = decision.room.delete_message(ctx, decision.message).await {
+                warn!("Couldn't delete the old prompt of a decision: {}", err);
+            }
+            decision.message = msg.id;
+            self.0
+                .metadata_mut()
+                .pending_decisions
+                .push(decision.clone());
+            decisions::spawn(ctx, msg, decision, self.rules().clone(), self.host());
+        }
+    }
+
     pub fn join(&mut self, id: UserId) -> JoinResult {
         if let Wrapper::NotStarted(s) = &mut self.0 {
             s.join(id)
@@ -127,6 +483,22 @@ impl Game {
         }
     }
 
+    pub fn join_waitlist(&mut self, id: UserId) -> JoinResult {
+        if let Wrapper::NotStarted(s) = &mut self.0 {
+            s.join_waitlist(id)
+        } else {
+            Err(JoinError::GameStarted)
+        }
+    }
+
+    /// Lets users on the waitlist into the game while there's room, returning who got in
+    pub fn fill_from_waitlist(&mut self) -> Vec<UserId> {
+        match &mut self.0 {
+            Wrapper::NotStarted(s) => s.fill_from_waitlist(),
+            _ => vec![],
+        }
+    }
+
     pub fn set_king_substitution_status(&mut self, st: SubstitutionStatus) {
         match &mut self.0 {
             Wrapper::ABlock(s) => s.set_king_substitution_status(st),
@@ -144,6 +516,10 @@ impl Game {
         }
     }
 
+    pub fn id(&self) -> u64 {
+        self.metadata().id
+    }
+
     pub fn host(&self) -> UserId {
         self.metadata().host
     }
@@ -164,6 +540,181 @@ impl Game {
         self.metadata().player_role
     }
 
+    pub fn rooms_category(&self) -> Option<ChannelId> {
+        self.metadata().rooms_category
+    }
+
+    /// Whether a picture of the game gets attached to `!gameinfo` and to block announcements
+    pub fn status_board_enabled(&self) -> bool {
+        self.metadata().status_board
+    }
+
+    pub fn rules(&self) -> &GameRules {
+        &self.metadata().rules
+    }
+
+    /// The name of the theme pack the game is played with
+    pub fn theme(&self) -> &str {
+        &self.metadata().theme
+    }
+
+    pub fn set_theme(&mut self, theme: String) {
+        match &mut self.0 {
+            Wrapper::NotStarted(ns) => ns.metadata.theme = theme,
+            other => warn!("set_theme got called in {:?}", other),
+        }
+    }
+
+    /// Makes the bot host the game itself, starting it at `starts_at`, or as soon as six players
+    /// joined if it's `None`
+    pub fn automate(&mut self, starts_at: Option<DateTime<Utc>>) {
+        self.0.metadata_mut().autopilot = Some(Autopilot {
+            next_step: starts_at,
+        });
+    }
+
+    /// Whether the bot hosts the game itself, see `crate::autopilot`
+    pub fn is_automated(&self) -> bool {
+        self.metadata().autopilot.is_some()
+    }
+
+    /// When a game the bot hosts takes its next step
+    pub fn next_step(&self) -> Option<DateTime<Utc>> {
+        self.metadata()
+            .autopilot
+            .as_ref()
+            .and_then(|autopilot| autopilot.next_step)
+    }
+
+    pub fn set_next_step(&mut self, at: DateTime<Utc>) {
+        match &mut self.0.metadata_mut().autopilot {
+            Some(autopilot) => autopilot.next_step = Some(at),
+            None => warn!("set_next_step got called on a game the bot doesn't host"),
+        }
+    }
+
+    /// Stops the game from moving on to the next time block until the players that are absent
+    /// were replaced or killed, see `crate::absence`
+    ///
+    /// The pending decisions are held meanwhile, see `decision_deadline`.
+    pub fn pause(&mut self) {
+        let paused_since = &mut self.0.metadata_mut().paused_since;
+        if paused_since.is_none() {
+            *paused_since = Some(Utc::now());
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.metadata().paused_since.is_some() && !self.absent_players().is_empty()
+    }
+
+    /// Lets the game move on again if the host dealt with every player that was absent, giving
+    /// the pending decisions back the time they were held for
+    pub fn resume_if_everyone_is_back(&mut self) {
+        if !self.absent_players().is_empty() {
+            return;
+        }
+
+        let metadata = self.0.metadata_mut();
+        if let Some(paused_since) = metadata.paused_since.take() {
+            let paused_for = Utc::now() - paused_since;
+            for decision in &mut metadata.pending_decisions {
+                decision.deadline = decision
+                    .deadline
+                    .checked_add_signed(paused_for)
+                    .unwrap_or(chrono::MAX_DATETIME);
+            }
+        }
+    }
+
+    /// When `decision` times out, which is pushed out by pausing the game, or `None` while the
+    /// game is paused
+    ///
+    /// Decisions that aren't pending anymore have timed out already.
+    fn decision_deadline(&self, decision: &PendingDecision) -> Option<DateTime<Utc>> {
+        let pending = self
+            .metadata()
+            .pending_decisions
+            .iter()
+            .find(|pending| pending.is(decision));
+
+        match pending {
+            Some(_) if self.is_paused() => None,
+            Some(pending) => Some(pending.deadline),
+            None => Some(Utc::now()),
+        }
+    }
+
+    /// Forgets about every decision that's being waited on, so nobody applies them anymore
+    fn drop_pending_decisions(&mut self) {
+        let pending = &mut self.0.metadata_mut().pending_decisions;
+        if !pending.is_empty() {
+            info!("Dropping {} pending decision(s)", pending.len());
+            pending.clear();
+        }
+    }
+
+    /// The players still alive that left the server or lost the player role
+    pub fn absent_players(&self) -> Vec<UserId> {
+        self.players()
+            .map(|players| {
+                players
+                    .values()
+                    .filter(|player| player.is_alive() && player.is_absent())
+                    .map(|player| player.id())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Makes the game start at `starts_at`, see `crate::lobby`
+    pub fn schedule(&mut self, starts_at: DateTime<Utc>, players_reminded: bool) {
+        self.0.metadata_mut().schedule = Some(Schedule {
+            starts_at,
+            players_reminded,
+        });
+    }
+
+    pub fn unschedule(&mut self) {
+        self.0.metadata_mut().schedule = None;
+    }
+
+    /// When a game made with `!schedule` starts, if that time didn't come yet
+    pub fn scheduled_start(&self) -> Option<DateTime<Utc>> {
+        self.metadata()
+            .schedule
+            .as_ref()
+            .map(|schedule| schedule.starts_at)
+    }
+
+    pub fn players_reminded(&self) -> bool {
+        self.metadata()
+            .schedule
+            .as_ref()
+            .map_or(false, |schedule| schedule.players_reminded)
+    }
+
+    pub fn set_players_reminded(&mut self) {
+        match &mut self.0.metadata_mut().schedule {
+            Some(schedule) => schedule.players_reminded = true,
+            None => warn!("set_players_reminded got called on a game that isn't scheduled"),
+        }
+    }
+
+    /// Where the message showing who's in the lobby was posted, if one was
+    pub fn lobby_message(&self) -> Option<(ChannelId, MessageId)> {
+        self.metadata().lobby_message
+    }
+
+    pub fn set_lobby_message(&mut self, channel: ChannelId, message: MessageId) {
+        self.0.metadata_mut().lobby_message = Some((channel, message));
+    }
+
+    /// The Discord objects that were made for this game, and have to be cleaned up after it
+    pub fn created_objects(&self) -> &CreatedObjects {
+        &self.metadata().created
+    }
+
     #[inline]
     fn metadata(&self) -> &Metadata {
         self.0.metadata()
@@ -253,6 +804,13 @@ impl Game {
         }
     }
 
+    pub fn waitlist(&self) -> Option<&Vec<UserId>> {
+        match &self.0 {
+            Wrapper::NotStarted(s) => Some(&s.state.waitlist),
+            _ => None,
+        }
+    }
+
     pub fn players(&self) -> Option<&BTreeMap<UserId, Player>> {
         for_all_blocks!(&self.0, s, s.players())
     }
@@ -270,7 +828,7 @@ impl Game {
 
     pub fn king_murder_target(&self) -> Option<UserId> {
         match &self.0 {
-            Wrapper::CBlock(c) => Some(c.king_murder_target()),
+            Wrapper::CBlock(c) => c.king_murder_target(),
             _ => None,
         }
     }
@@ -293,7 +851,7 @@ impl Game {
 /// The "low-level" struct that drives the logic for the state machine
 ///
 /// State transitions are represented through next() methods implemented on "specializations" of this type
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct GameMachine<S>
 where
     S: GameState + Clone,
@@ -353,21 +911,83 @@ where
 }
 
 /// Stores Discord related information about a game
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct Metadata {
+    id: u64,
     guild: GuildId,
     meeting_room: ChannelId,
     announcement_channel: ChannelId,
     host: UserId,
     player_role: RoleId,
     delete_rooms_category_on_game_end: bool,
+    #[serde(default)]
+    status_board: bool,
+    rooms_category: Option<ChannelId>,
+    rooms_category_name: String,
+    rules: GameRules,
+    #[serde(default = "default_theme")]
+    theme: String,
+    created: CreatedObjects,
+    /// The decisions players are being waited on for
+    #[serde(default)]
+    pending_decisions: Vec<PendingDecision>,
+    /// Set when the bot hosts the game itself
+    #[serde(default)]
+    autopilot: Option<Autopilot>,
+    /// Set for lobbies made with `!schedule`, until the time they were scheduled for comes
+    #[serde(default)]
+    schedule: Option<Schedule>,
+    /// The message that shows who's in the lobby, see `crate::lobby`
+    #[serde(default)]
+    lobby_message: Option<(ChannelId, MessageId)>,
+    /// Set when the host paused the game to deal with players that are absent
+    #[serde(default)]
+    paused_since: Option<DateTime<Utc>>,
+    /// Avatar images rendered for target choices, which are cheap to render again after a restart
+    #[serde(skip)]
+    composites: CompositeCache,
+}
+
+/// How a game the bot hosts itself moves along
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Autopilot {
+    /// When the game starts, moves on to the next time block, or gets cleaned up after it ended,
+    /// depending on where it's at. A lobby without one starts as soon as it's full
+    next_step: Option<DateTime<Utc>>,
+}
+
+/// When a scheduled game starts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Schedule {
+    starts_at: DateTime<Utc>,
+    /// Whether the players were pinged about the game starting soon
+    players_reminded: bool,
+}
+
+impl Metadata {
+    /// Makes `player` decide by reacting to `msg`, keeping track of it so it's not lost to a restart
+    fn wait_for_decision(
+        &mut self,
+        ctx: &Context,
+        msg: Message,
+        kind: DecisionKind,
+        player: UserId,
+    ) {
+        let decision = PendingDecision::new(kind, player, &msg, &self.rules);
+        self.pending_decisions.push(decision.clone());
+        decisions::spawn(ctx, msg, decision, self.rules.clone(), self.host);
+    }
+}
+
+fn default_theme() -> String {
+    themes::DEFAULT_THEME.to_string()
 }
 
 /// Marker trait for a struct that represents a valid game state
 pub trait GameState: std::fmt::Debug {}
 
 /// Enum whose only purpose is to wrap the various type-states in a single type
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 enum Wrapper {
     NotStarted(GameMachine<NotStarted>),
     ABlock(GameMachine<ABlock>),
@@ -394,6 +1014,20 @@ impl Wrapper {
         }
     }
 
+    #[inline]
+    fn metadata_mut(&mut self) -> &mut Metadata {
+        match self {
+            Wrapper::NotStarted(s) => &mut s.metadata,
+            Wrapper::ABlock(s) => &mut s.metadata,
+            Wrapper::BBlock(s) => &mut s.metadata,
+            Wrapper::CBlock(s) => &mut s.metadata,
+            Wrapper::DBlock(s) => &mut s.metadata,
+            Wrapper::EBlock(s) => &mut s.metadata,
+            Wrapper::FBlock(s) => &mut s.metadata,
+            Wrapper::GameEnded(s) => &mut s.metadata,
+        }
+    }
+
     async fn next(self, ctx: &Context) -> Self {
         match self {
             Wrapper::ABlock(s) => s.next(ctx).await.wrap(),
@@ -4,8 +4,9 @@
 
 use super::{macros::state::*, *};
 use crate::{
-    game::roles::{Role, RoleHolder},
+    game::{item::WATCH_COLOURS, roles::RoleHolder, CreatedObjects},
     helpers::perms,
+    i18n, themes,
 };
 
 use rand::{seq::SliceRandom, thread_rng};
@@ -19,9 +20,12 @@ use serenity::{
 use std::fmt;
 
 /// Struct which represents the state of the game when it is not started
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub(super) struct NotStarted {
     pub(super) joined_users: Vec<UserId>,
+    /// The users that want to play once there's room for them, in the order they signed up in
+    #[serde(default)]
+    pub(super) waitlist: Vec<UserId>,
 }
 
 impl NotStarted {
@@ -37,36 +41,63 @@ impl GameMachine<NotStarted> {
     pub(super) async fn next(mut self, ctx: &Context) -> CommandResult<GameMachine<ABlock>> {
         debug_assert!(self.state.can_start());
 
-        use crate::game::roles::{King, Knight, Prince, Revolutionary, Sorcerer, TheDouble};
-        let mut roles: Vec<RoleHolder> = vec![
-            King.wrap(),
-            Knight.wrap(),
-            Prince.wrap(),
-            Revolutionary.wrap(),
-            Sorcerer.wrap(),
-            TheDouble.wrap(),
-        ];
+        let mut created = CreatedObjects::default();
 
+        match self.set_up_rooms(ctx, &mut created).await {
+            Ok(players) => {
+                self.metadata.created = created;
+                Ok(GameMachine::<ABlock> {
+                    state: ABlock::new(players, 0, SubstitutionStatus::HasNot),
+                    metadata: self.metadata,
+                })
+            }
+            Err(err) => {
+                warn!("Couldn't start the game ({}), rolling back...", err);
+                created
+                    .roll_back(ctx, self.metadata.guild, self.metadata.player_role)
+                    .await;
+                Err(err)
+            }
+        }
+    }
+
+    /// Makes the players' rooms and gives them the player role, writing down everything it
+    /// creates in `created` so it can be undone if anything goes wrong
+    async fn set_up_rooms(
+        &mut self,
+        ctx: &Context,
+        created: &mut CreatedObjects,
+    ) -> CommandResult<BTreeMap<UserId, Player>> {
+        let mut roles = RoleHolder::all();
         roles.shuffle(&mut thread_rng());
 
-        // I'm a sucker for plot accuracy, these should be all
-        let watch_colours = vec!["blue", "beige", "orange", "green", "black", "red"];
         let mut current_room: u8 = 1;
 
         let at_everyone_perms = perms::make_denied_override_for_role(RoleId {
             0: self.metadata.guild.0,
         });
 
-        let rooms_category = self
-            .metadata
-            .guild
-            .create_channel(ctx, |c| c.name("Rooms").kind(ChannelType::Category))
-            .await?
-            .id;
+        let rooms_category = match self.metadata.rooms_category {
+            Some(category) => category,
+            None => {
+                let category = self
+                    .metadata
+                    .guild
+                    .create_channel(ctx, |c| {
+                        c.name(&self.metadata.rooms_category_name)
+                            .kind(ChannelType::Category)
+                    })
+                    .await?
+                    .id;
+                created.category = Some(category);
+                category
+            }
+        };
 
-        let mut next = ABlock::new(BTreeMap::new(), 0, SubstitutionStatus::HasNot);
+        let mut players = BTreeMap::new();
+        let theme = themes::get(ctx, &self.metadata.theme).await;
 
-        for new_player in self.state.joined_users.iter_mut().zip(watch_colours.iter()) {
+        for new_player in self.state.joined_users.iter_mut().zip(WATCH_COLOURS.iter()) {
             let channel = self
                 .metadata
                 .guild
@@ -75,14 +106,16 @@ impl GameMachine<NotStarted> {
                         .category(rooms_category)
                 })
                 .await?;
+            created.channels.push(channel.id);
 
-            next.players_mut().insert(
+            players.insert(
                 *new_player.0,
                 Player::new(
                     *new_player.0,
                     roles.remove(0),
                     channel.id,
                     new_player.1.to_string(),
+                    &self.metadata.rules,
                 ),
             );
 
@@ -92,6 +125,7 @@ impl GameMachine<NotStarted> {
                 .await?
                 .add_role(ctx, self.metadata.player_role)
                 .await?;
+            created.members_with_role.push(*new_player.0);
 
             channel.create_permission(ctx, &at_everyone_perms).await?;
 
@@ -102,26 +136,28 @@ impl GameMachine<NotStarted> {
                 )
                 .await?;
 
-            channel.say(ctx, format!(r#"
-You look around the room you see yourself in. You see a toilet and a washbowl, a table with a jute bag on top of it in the center of the room, and a 20-inch screen in the center of the room.
-
-You reach inside the bag and take out one item after another.
-A ball-point pen.
-A memo book.
-A {} digital watch.
-Seven portions of solid food.
-Some kind of a tablet.
-
-And a heavy-duty knife.
-            "#, new_player.1)).await?;
+            let locale = i18n::locale(ctx, Some(self.metadata.guild), Some(*new_player.0)).await;
+            channel
+                .say(
+                    ctx,
+                    theme.tr_with(
+                        &locale,
+                        "room.intro",
+                        &[
+                            (
+                                "watch",
+                                &i18n::tr(&locale, &format!("watch.{}", new_player.1)),
+                            ),
+                            ("food", &self.metadata.rules.food_portions),
+                        ],
+                    ),
+                )
+                .await?;
 
             current_room += 1;
         }
 
-        Ok(GameMachine::<ABlock> {
-            state: next,
-            metadata: self.metadata,
-        })
+        Ok(players)
     }
 
     #[inline]
@@ -144,7 +180,37 @@ And a heavy-duty knife.
         }
     }
 
+    /// Puts `id` at the end of the waitlist of a full game
+    pub fn join_waitlist(&mut self, id: UserId) -> JoinResult {
+        if id == self.metadata.host {
+            Err(JoinError::YoureTheHost)
+        } else if self.state.joined_users.contains(&id) || self.state.waitlist.contains(&id) {
+            Err(JoinError::AlreadyIn)
+        } else {
+            self.state.waitlist.push(id);
+            Ok(())
+        }
+    }
+
+    /// Lets the first users on the waitlist into the game while there's room for them, returning
+    /// who got in
+    pub fn fill_from_waitlist(&mut self) -> Vec<UserId> {
+        let free_slots = 6usize.saturating_sub(self.state.joined_users.len());
+        let admitted = self
+            .state
+            .waitlist
+            .drain(..free_slots.min(self.state.waitlist.len()))
+            .collect::<Vec<_>>();
+        self.state.joined_users.extend(&admitted);
+        admitted
+    }
+
     pub fn leave(&mut self, id: UserId) -> LeaveResult {
+        if let Some(idx) = self.state.waitlist.iter().position(|user| *user == id) {
+            self.state.waitlist.remove(idx);
+            return Ok(());
+        }
+
         if id == self.metadata.host {
             Err(LeaveError::YoureTheHost)
         } else if !self.state.joined_users.contains(&id) {
@@ -189,6 +255,19 @@ impl fmt::Display for JoinError {
 
 impl std::error::Error for JoinError {}
 
+impl JoinError {
+    /// The catalogue key of the text telling the user why they can't join
+    pub fn key(&self) -> &'static str {
+        use JoinError::*;
+        match self {
+            GameFull => "join.game_full",
+            YoureTheHost => "join.host",
+            AlreadyIn => "join.already_in",
+            GameStarted => "join.game_started",
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug)]
 pub enum LeaveError {
     NotInAGame,
@@ -209,3 +288,15 @@ impl fmt::Display for LeaveError {
         }
     }
 }
+
+impl LeaveError {
+    /// The catalogue key of the text telling the user why they can't leave
+    pub fn key(&self) -> &'static str {
+        use LeaveError::*;
+        match self {
+            NotInAGame => "leave.not_in_a_game",
+            YoureTheHost => "leave.host",
+            GameStarted => "leave.game_started",
+        }
+    }
+}
@@ -1,6 +1,14 @@
+use super::GameRules;
+
+use serde::{Deserialize, Serialize};
+
 use tracing::{error, instrument};
 
-#[derive(Debug, Clone)]
+/// The colours of the players' watches, in the order of the rooms they're given to
+// I'm a sucker for plot accuracy, these should be all
+pub const WATCH_COLOURS: [&str; 6] = ["blue", "beige", "orange", "green", "black", "red"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Item {
     pub name: String,
     pub edible: bool,
@@ -61,24 +69,24 @@ impl Item {
 
 type Count = u8;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Items {
     items: Vec<(Count, Item)>,
     memo_book: MemoBook,
 }
 
 impl Items {
-    pub fn new(watch_colour: String) -> Self {
+    pub fn new(watch_colour: String, rules: &GameRules) -> Self {
         Self {
             items: vec![
-                (7, Item::food()),
+                (rules.food_portions, Item::food()),
                 (1, Item::watch(watch_colour)),
                 (1, Item::knife()),
                 (1, Item::tablet()),
                 (1, Item::memo_book()),
                 (1, Item::ballpoint_pen()),
             ],
-            memo_book: MemoBook::new(),
+            memo_book: MemoBook::new(rules.max_notes),
         }
     }
 
@@ -86,6 +94,13 @@ impl Items {
         self.items.iter()
     }
 
+    /// The colour of the watch the player was given at the start of the game
+    pub fn watch_colour(&self) -> Option<&str> {
+        self.items
+            .iter()
+            .find_map(|(_, item)| item.name.strip_suffix(" watch"))
+    }
+
     #[instrument]
     pub fn get_item(&self, item_name: &str) -> &(Count, Item) {
         for item in self.items.iter() {
@@ -123,14 +138,15 @@ impl Items {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MemoBook {
     notes: Vec<Note>,
+    max_notes: u32,
     notes_ripped_from_self: u32,
     ripped_notes_gotten_from_others: u32,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Note {
     pub text: String,
     pub when: String,
@@ -138,18 +154,17 @@ pub struct Note {
 }
 
 impl MemoBook {
-    const MAX_NOTES: u32 = 128; // PONDER: Should this be configurable per game?
-
-    pub fn new() -> Self {
+    pub fn new(max_notes: u32) -> Self {
         Self {
             notes: vec![],
+            max_notes,
             notes_ripped_from_self: 0,
             ripped_notes_gotten_from_others: 0,
         }
     }
 
     pub fn add_note(&mut self, text: String, when: String) -> Result<(), String> {
-        if self.notes.len() < (Self::MAX_NOTES - self.notes_ripped_from_self) as usize {
+        if self.notes.len() < self.max_notes.saturating_sub(self.notes_ripped_from_self) as usize {
             self.notes.push(Note {
                 text,
                 when,
@@ -180,7 +195,7 @@ impl MemoBook {
 
     pub fn rip_note(&mut self, idx: usize) -> Option<Note> {
         self.notes_ripped_from_self += 1;
-        if idx < (Self::MAX_NOTES - self.notes_ripped_from_self) as usize {
+        if idx < self.max_notes.saturating_sub(self.notes_ripped_from_self) as usize {
             Some(self.notes.remove(idx))
         } else {
             None
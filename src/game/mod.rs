@@ -1,10 +1,15 @@
 mod data;
 mod fsm;
 pub mod item;
+mod objects;
 mod player;
 mod roles;
+mod rules;
+pub mod simulation;
 
 pub use data::{DeathCause, SubstitutionStatus};
-pub use fsm::Game;
+pub use fsm::{Game, JoinError, LeaveError};
+pub use objects::CreatedObjects;
 pub use player::{Player, SecretMeeting};
 pub use roles::{King, RoleHolder, RoleName};
+pub use rules::{AssistantDefault, GameRules, TargetDefault};
@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+use serenity::{
+    model::id::{ChannelId, GuildId, RoleId, UserId},
+    prelude::*,
+};
+use tracing::{info, warn};
+
+/// Discord objects the bot made (or changed) for a game
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CreatedObjects {
+    /// The category holding the players' rooms, if it was made for this game
+    pub category: Option<ChannelId>,
+    /// The players' rooms, the secret meeting rooms and their categories
+    pub channels: Vec<ChannelId>,
+    /// Members that were given the player role
+    pub members_with_role: Vec<UserId>,
+}
+
+impl CreatedObjects {
+    /// Undoes everything that was written down, going on even if some of it fails
+    pub async fn roll_back(&self, ctx: &Context, guild: GuildId, player_role: RoleId) {
+        for member in &self.members_with_role {
+            if let Err(err) = ctx
+                .http
+                .remove_member_role(guild.0, member.0, player_role.0)
+                .await
+            {
+                warn!("Couldn't take the player role from {}: {}", member, err);
+            }
+        }
+
+        for channel in &self.channels {
+            if let Err(err) = channel.delete(ctx).await {
+                warn!("Couldn't delete channel {}: {}", channel, err);
+            }
+        }
+
+        if let Some(category) = self.category {
+            if let Err(err) = category.delete(ctx).await {
+                warn!("Couldn't delete category {}: {}", category, err);
+            }
+        }
+
+        info!("Rolled back {:?}", self);
+    }
+}
@@ -2,10 +2,13 @@ use std::fmt::Debug;
 
 use super::{
     fsm::TimeBlock,
-    item::{Item, Items},
+    item::{Item, Items, WATCH_COLOURS},
     roles::{RoleHolder, RoleName},
-    DeathCause,
+    DeathCause, GameRules,
 };
+use crate::{i18n, themes::Theme};
+
+use serde::{Deserialize, Serialize};
 use serenity::{
     framework::standard::CommandResult,
     model::id::{ChannelId, UserId},
@@ -14,7 +17,7 @@ use serenity::{
 
 pub type SecretMeeting = Option<(UserId, ChannelId)>;
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Player {
     id: UserId,
     role: RoleHolder,
@@ -23,10 +26,19 @@ pub struct Player {
     secret_meeting_partner: Option<UserId>,
     secret_meeting_channels: Vec<(SecretMeeting, SecretMeeting)>,
     items: Items,
+    /// Set while the player isn't in the server or doesn't have the player role
+    #[serde(default)]
+    absent: bool,
 }
 
 impl Player {
-    pub fn new(id: UserId, role: RoleHolder, room: ChannelId, watch_colour: String) -> Self {
+    pub fn new(
+        id: UserId,
+        role: RoleHolder,
+        room: ChannelId,
+        watch_colour: String,
+        rules: &GameRules,
+    ) -> Self {
         // PONDER: We may want to allow disabling certain items
         //         If we do, how should that be handled? Should we just pass a reference to the Game and ask it for enabled items?
         Self {
@@ -36,7 +48,8 @@ impl Player {
             alive: true,
             secret_meeting_partner: None,
             secret_meeting_channels: vec![],
-            items: Items::new(watch_colour),
+            items: Items::new(watch_colour, rules),
+            absent: false,
         }
     }
 
@@ -48,6 +61,18 @@ impl Player {
         self.room
     }
 
+    /// The colour of the watch the player was given at the start of the game
+    pub fn watch_colour(&self) -> Option<&str> {
+        self.items.watch_colour()
+    }
+
+    /// The number of the player's room, which is decided by the colour of their watch
+    pub fn room_number(&self) -> Option<u8> {
+        let colour = self.watch_colour()?;
+        let idx = WATCH_COLOURS.iter().position(|c| *c == colour)?;
+        Some(idx as u8 + 1)
+    }
+
     pub fn secret_meeting_partner(&self) -> Option<UserId> {
         self.secret_meeting_partner
     }
@@ -58,7 +83,9 @@ impl Player {
 
     pub fn add_secret_meeting(&mut self, day: u8, channel: ChannelId) {
         let day = day as usize;
-        self.secret_meeting_channels.resize(day, (None, None));
+        if self.secret_meeting_channels.len() <= day {
+            self.secret_meeting_channels.resize(day + 1, (None, None));
+        }
         let secret_meetings_for_day = self
             .secret_meeting_channels
             .get_mut(day)
@@ -72,6 +99,48 @@ impl Player {
         }
     }
 
+    /// Every secret meeting the player had, as the day, which meeting of the day it was (1 or 2)
+    /// and the partner
+    pub fn secret_meetings(&self) -> impl Iterator<Item = (u8, u8, UserId)> + '_ {
+        self.secret_meeting_channels
+            .iter()
+            .enumerate()
+            .flat_map(|(day, (first, second))| {
+                let first = first.map(|(partner, _)| (day as u8, 1, partner));
+                let second = second.map(|(partner, _)| (day as u8, 2, partner));
+                first.into_iter().chain(second)
+            })
+    }
+
+    /// The rooms of every secret meeting the player was a guest in, with the partner that hosted it
+    pub fn secret_meeting_rooms(&self) -> impl Iterator<Item = (UserId, ChannelId)> + '_ {
+        self.secret_meeting_channels
+            .iter()
+            .flat_map(|(first, second)| first.iter().chain(second.iter()).copied())
+    }
+
+    /// Makes everything the player knows about `old` point to `new`, who took their seat
+    ///
+    /// This includes the player's own ID, if they're the one that was replaced.
+    pub fn replace_user(&mut self, old: UserId, new: UserId) {
+        if self.id == old {
+            self.id = new;
+        }
+        if self.secret_meeting_partner == Some(old) {
+            self.secret_meeting_partner = Some(new);
+        }
+
+        let meetings = self
+            .secret_meeting_channels
+            .iter_mut()
+            .flat_map(|(first, second)| std::iter::once(first).chain(std::iter::once(second)));
+        for (partner, _) in meetings.flatten() {
+            if *partner == old {
+                *partner = new;
+            }
+        }
+    }
+
     pub fn get_secret_meetings_for_day(&self, day: u8) -> Option<&(SecretMeeting, SecretMeeting)> {
         self.secret_meeting_channels.get(day as usize)
     }
@@ -85,16 +154,27 @@ impl Player {
         cause: DeathCause,
         ctx: &Context,
         channel: ChannelId,
+        theme: &Theme,
     ) -> CommandResult {
         self.alive = false;
 
+        let guild = channel.to_channel(ctx).await?.guild().map(|c| c.guild_id);
+        let locale = i18n::locale(ctx, guild, None).await;
         channel
-            .say(ctx, format!("{} {}", self.id.mention(), cause))
+            .say(ctx, cause.describe(theme, &locale, self.id))
             .await?;
         Ok(())
     }
 
-    /// Function meant to be only used inside test functions
+    pub fn is_absent(&self) -> bool {
+        self.absent
+    }
+
+    pub fn set_absent(&mut self, absent: bool) {
+        self.absent = absent;
+    }
+
+    /// Kills the player without telling anyone, for tests and simulations
     pub fn set_dead_mock(&mut self) {
         self.alive = false;
     }
@@ -130,6 +210,7 @@ impl Debug for Player {
             .field("secret_meeting_partner", &self.secret_meeting_partner)
             .field("secret_meeting_channels", &self.secret_meeting_channels)
             .field("items", &self.items)
+            .field("absent", &self.absent)
             .finish()
     }
 }
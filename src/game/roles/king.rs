@@ -1,7 +1,7 @@
 use super::prelude::*;
 use crate::game::fsm::TimeBlock;
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct King;
 
 impl Role for King {
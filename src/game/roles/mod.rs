@@ -9,6 +9,8 @@ mod sorcerer;
 
 pub use role::{Role, RoleName};
 
+use serde::{Deserialize, Serialize};
+
 pub use double::TheDouble;
 pub use king::King;
 pub use knight::Knight;
@@ -16,7 +18,7 @@ pub use prince::Prince;
 pub use revolutionary::Revolutionary;
 pub use sorcerer::Sorcerer;
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub enum RoleHolder {
     King(King),
     Double(TheDouble),
@@ -27,6 +29,18 @@ pub enum RoleHolder {
 }
 
 impl RoleHolder {
+    /// One of every role, as every game has each of them exactly once
+    pub fn all() -> Vec<RoleHolder> {
+        vec![
+            King.wrap(),
+            Knight.wrap(),
+            Prince.wrap(),
+            Revolutionary.wrap(),
+            Sorcerer.wrap(),
+            TheDouble.wrap(),
+        ]
+    }
+
     pub fn name(&self) -> RoleName {
         match &self {
             RoleHolder::King(r) => r.name(),
@@ -53,4 +67,5 @@ impl RoleHolder {
 mod prelude {
     pub use super::{Role, RoleHolder, RoleName};
     pub use crate::game::{DeathCause, Game, Player};
+    pub use serde::{Deserialize, Serialize};
 }
@@ -18,6 +18,18 @@ impl RoleName {
             RoleName::King | RoleName::TheDouble | RoleName::Prince
         )
     }
+
+    /// The role's name in the catalogue, theme packs and `res/`
+    pub fn key(&self) -> &'static str {
+        match self {
+            RoleName::King => "king",
+            RoleName::Prince => "prince",
+            RoleName::TheDouble => "the_double",
+            RoleName::Sorcerer => "sorcerer",
+            RoleName::Knight => "knight",
+            RoleName::Revolutionary => "revolutionary",
+        }
+    }
 }
 
 impl ToString for RoleName {
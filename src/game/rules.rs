@@ -0,0 +1,129 @@
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr, time::Duration};
+
+/// Knobs that change how a game plays out
+///
+/// The defaults follow the game as it is described in the book.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(default)]
+pub struct GameRules {
+    /// The amount of solid food portions each player finds in their bag
+    pub food_portions: u8,
+    /// How many notes can be written in a memo book
+    pub max_notes: u32,
+    /// How long players have to make a decision, like choosing a target, in minutes
+    pub decision_timeout_minutes: u64,
+    /// How many minutes before the deadline players get reminded of a decision they have to make
+    pub decision_reminder_minutes: u64,
+    /// What happens when players don't decide in time
+    pub decision_defaults: DecisionDefaults,
+    /// How long every time block lasts in games the bot hosts itself, in minutes
+    pub block_minutes: u64,
+}
+
+impl GameRules {
+    pub fn decision_timeout(&self) -> Duration {
+        Duration::from_secs(self.decision_timeout_minutes * 60)
+    }
+
+    pub fn decision_reminder(&self) -> Duration {
+        Duration::from_secs(self.decision_reminder_minutes * 60)
+    }
+
+    pub fn block_length(&self) -> Duration {
+        Duration::from_secs(self.block_minutes * 60)
+    }
+}
+
+impl Default for GameRules {
+    fn default() -> Self {
+        Self {
+            food_portions: 7,
+            max_notes: 128,
+            decision_timeout_minutes: 30,
+            decision_reminder_minutes: 5,
+            decision_defaults: DecisionDefaults::default(),
+            block_minutes: 30,
+        }
+    }
+}
+
+/// The outcomes of decisions that weren't made in time
+///
+/// Players that don't pick a secret meeting partner always get a random one, as every player has
+/// to have a partner.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(default)]
+pub struct DecisionDefaults {
+    /// Whom the King-like player targets for 「 Murder 」
+    pub murder_target: TargetDefault,
+    /// Whether the Sorcerer or the Knight carry out the 「 Murder 」
+    pub assistant: AssistantDefault,
+    /// Whom the Revolutionary targets for 「 Assassination 」
+    pub assassination_target: TargetDefault,
+}
+
+impl Default for DecisionDefaults {
+    fn default() -> Self {
+        Self {
+            murder_target: TargetDefault::Nobody,
+            assistant: AssistantDefault::Decline,
+            assassination_target: TargetDefault::Nobody,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TargetDefault {
+    Nobody,
+    /// A random alive player, other than the one choosing
+    Random,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AssistantDefault {
+    Decline,
+    Accept,
+}
+
+impl fmt::Display for TargetDefault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TargetDefault::Nobody => write!(f, "nobody"),
+            TargetDefault::Random => write!(f, "random"),
+        }
+    }
+}
+
+impl FromStr for TargetDefault {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "nobody" | "none" => Ok(TargetDefault::Nobody),
+            "random" => Ok(TargetDefault::Random),
+            _ => Err("You need to write either `nobody` or `random`"),
+        }
+    }
+}
+
+impl fmt::Display for AssistantDefault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AssistantDefault::Decline => write!(f, "decline"),
+            AssistantDefault::Accept => write!(f, "accept"),
+        }
+    }
+}
+
+impl FromStr for AssistantDefault {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "decline" | "no" => Ok(AssistantDefault::Decline),
+            "accept" | "yes" => Ok(AssistantDefault::Accept),
+            _ => Err("You need to write either `decline` or `accept`"),
+        }
+    }
+}
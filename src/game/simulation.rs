@@ -0,0 +1,175 @@
+//! Plays games out without Discord, with every decision made at random
+//!
+//! It follows the same rules as the state machine: every day the highest ranking King-like player
+//! picks a target for 「 Murder 」, which gets carried out if the Sorcerer or the Knight agree to it,
+//! then everyone eats a portion of food or starves, and the Revolutionary picks a target for
+//! 「 Assassination 」. Secret meetings, stabbing and 「 Substitution 」 aren't played, as what they
+//! change depends on how people talk to each other.
+
+use super::{
+    fsm::{GameState, TimeBlock},
+    item::{Item, WATCH_COLOURS},
+    roles::{RoleHolder, RoleName},
+    GameRules, Player,
+};
+
+use rand::{
+    seq::{IteratorRandom, SliceRandom},
+    Rng,
+};
+use serenity::model::id::{ChannelId, UserId};
+use std::collections::BTreeMap;
+
+/// How a simulated player died
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Death {
+    Murder,
+    Assassination,
+    Starvation,
+}
+
+/// What happened in a simulated game
+#[derive(Debug)]
+pub struct Outcome {
+    /// The day the game ended on, counted from 0
+    pub day: u8,
+    /// The roles of the players that were alive at the end, who all won
+    pub winners: Vec<RoleName>,
+    /// Who died, in order, with the day they died on, counted from 0
+    pub deaths: Vec<(u8, RoleName, Death)>,
+}
+
+#[derive(Debug)]
+struct Simulation {
+    players: BTreeMap<UserId, Player>,
+    day: u8,
+    deaths: Vec<(u8, RoleName, Death)>,
+}
+
+impl GameState for Simulation {}
+crate::impl_timeblock!(Simulation);
+
+/// Plays a whole game with `rules`
+pub fn simulate<R: Rng>(rules: &GameRules, rng: &mut R) -> Outcome {
+    let mut roles = RoleHolder::all();
+    roles.shuffle(rng);
+
+    let players = roles
+        .into_iter()
+        .zip(WATCH_COLOURS.iter())
+        .enumerate()
+        .map(|(idx, (role, colour))| {
+            let id = UserId(idx as u64 + 1);
+            let room = ChannelId(idx as u64 + 1);
+            (id, Player::new(id, role, room, colour.to_string(), rules))
+        })
+        .collect();
+
+    let mut game = Simulation {
+        players,
+        day: 0,
+        deaths: vec![],
+    };
+
+    // Days are capped so absurd amounts of food can't make this run forever
+    while !game.all_alive_have_won() && game.day < u8::MAX {
+        game.murder(rng);
+        if game.all_alive_have_won() {
+            break;
+        }
+
+        game.eat_or_starve();
+        game.assassinate(rng);
+        game.day += 1;
+    }
+
+    Outcome {
+        day: game.day,
+        winners: game
+            .players
+            .values()
+            .filter(|player| player.is_alive())
+            .map(Player::role_name)
+            .collect(),
+        deaths: game.deaths,
+    }
+}
+
+impl Simulation {
+    fn murder<R: Rng>(&mut self, rng: &mut R) {
+        // Murder needs both the Sorcerer and the Knight, just like in a real game
+        if !self.is_sorcerer_alive() || !self.is_knight_alive() {
+            return;
+        }
+
+        let king = [RoleName::King, RoleName::TheDouble, RoleName::Prince]
+            .iter()
+            .find_map(|role| self.alive_player_with(*role));
+        let king = match king {
+            Some(king) => king,
+            None => return,
+        };
+
+        if let Some(target) = self.pick_target(king, rng) {
+            // The Sorcerer or the Knight, whoever gets asked, either agrees or refuses
+            if rng.gen_bool(0.5) {
+                self.kill(target, Death::Murder);
+            }
+        }
+    }
+
+    fn eat_or_starve(&mut self) {
+        let mut starved = vec![];
+        for (id, player) in self.players.iter_mut().filter(|(_, p)| p.is_alive()) {
+            let food = player.items_mut().get_item_mut(Item::FOOD_NAME);
+            if food.0 > 0 {
+                food.0 -= 1;
+            } else {
+                starved.push(*id);
+            }
+        }
+
+        for id in starved {
+            self.kill(id, Death::Starvation);
+        }
+    }
+
+    fn assassinate<R: Rng>(&mut self, rng: &mut R) {
+        let revolutionary = match self.alive_player_with(RoleName::Revolutionary) {
+            Some(revolutionary) => revolutionary,
+            None => return,
+        };
+
+        if let Some(target) = self.pick_target(revolutionary, rng) {
+            self.kill(target, Death::Assassination);
+        }
+    }
+
+    /// Picks nobody or any alive player other than `chooser`, all being as likely
+    fn pick_target<R: Rng>(&self, chooser: UserId, rng: &mut R) -> Option<UserId> {
+        let candidates = self
+            .players
+            .values()
+            .filter(|player| player.is_alive() && player.id() != chooser)
+            .map(|player| Some(player.id()));
+
+        std::iter::once(None)
+            .chain(candidates)
+            .choose(rng)
+            .flatten()
+    }
+
+    fn alive_player_with(&self, role: RoleName) -> Option<UserId> {
+        self.players
+            .values()
+            .find(|player| player.is_alive() && player.role_name() == role)
+            .map(Player::id)
+    }
+
+    fn kill(&mut self, id: UserId, death: Death) {
+        if let Some(player) = self.players.get_mut(&id) {
+            player.set_dead_mock();
+            self.deaths.push((self.day, player.role_name(), death));
+        }
+    }
+}
@@ -1,20 +1,23 @@
-use std::collections::BTreeMap;
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap},
+    fmt,
+    sync::{Arc, Mutex},
+};
 
-use super::Error;
+use super::{
+    compositor::{self, Image},
+    Error,
+};
 use crate::{
-    data::{Cdn, ReqwestClient},
+    data::{Reqwest, ReqwestClient},
     game::Player,
+    metrics,
+    storage::{AvatarCache, AvatarCacheContainer},
 };
-use image::{
-    self, imageops, imageops::colorops, load_from_memory_with_format, png::PngEncoder, ColorType,
-    DynamicImage, ImageFormat,
-};
-use libwebp_image::webp_load_from_memory;
 use serenity::{builder::CreateEmbed, http::AttachmentType, model::id::UserId, prelude::*};
 use tracing::{error, info, warn};
 
-type Image = image::RgbaImage;
-
 /// Trait that exists to make !testk's existence easier on me
 pub trait Players: Sync {
     fn players(&self) -> Vec<Player>;
@@ -40,6 +43,57 @@ impl Players for BTreeMap<UserId, Player> {
     }
 }
 
+/// The file name the avatars image is attached to target choice messages with
+const AVATARS_FILE_NAME: &str = "avatars.png";
+
+/// An embed for choosing a target, along with the image it shows
+///
+/// The image has to be attached to the same message as the embed, see [`TargetChoice::attachment`].
+pub struct TargetChoice {
+    pub embed: CreateEmbed,
+    avatars_png: Arc<Vec<u8>>,
+}
+
+impl TargetChoice {
+    pub fn attachment(&self) -> AttachmentType<'_> {
+        AttachmentType::Bytes {
+            data: Cow::Borrowed(self.avatars_png.as_slice()),
+            filename: AVATARS_FILE_NAME.to_string(),
+        }
+    }
+}
+
+/// The players, in order, and whether each of them is alive
+type CompositeKey = (Vec<UserId>, Vec<bool>);
+
+/// The avatar images a game already rendered
+///
+/// Nobody can join a game after it starts, so the image only changes when someone dies, which
+/// means most prompts of a day can reuse the image of the first one.
+#[derive(Default, Clone)]
+pub struct CompositeCache {
+    composites: Arc<Mutex<HashMap<CompositeKey, Arc<Vec<u8>>>>>,
+}
+
+impl CompositeCache {
+    fn get(&self, key: &CompositeKey) -> Option<Arc<Vec<u8>>> {
+        self.composites.lock().unwrap().get(key).cloned()
+    }
+
+    fn insert(&self, key: CompositeKey, png: Arc<Vec<u8>>) {
+        self.composites.lock().unwrap().insert(key, png);
+    }
+}
+
+impl fmt::Debug for CompositeCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let len = self.composites.lock().unwrap().len();
+        f.debug_struct("CompositeCache")
+            .field("composites", &len)
+            .finish()
+    }
+}
+
 /// This function takes UserIds and generates an Embed containing
 /// * the avatars of the users indicated by those UserIds, placed one after each other
 /// * emojis indicating with what reaction you need to reply to select a specific player
@@ -48,155 +102,140 @@ pub async fn build_embed_for_target_choice(
     ctx: &Context,
     players: &dyn Players,
     embed_title: &str,
-) -> Result<CreateEmbed, Error> {
-    info!("Fetching avatars...");
-    let avatars = fetch_avatars(ctx, &players.player_ids()).await?;
-
-    let alivenesses = {
-        let mut a = vec![];
-        for player in &players.players() {
-            a.push(player.is_alive());
+    composites: &CompositeCache,
+) -> Result<TargetChoice, Error> {
+    let players = players.players();
+    let alivenesses = players.iter().map(Player::is_alive).collect::<Vec<_>>();
+    let key = (
+        players.iter().map(Player::id).collect(),
+        alivenesses.clone(),
+    );
+
+    let avatars_png = match composites.get(&key) {
+        Some(png) => {
+            info!("Reusing the avatars image");
+            png
+        }
+        None => {
+            info!("Fetching avatars...");
+            let avatars = fetch_avatars(ctx, &players).await;
+            // Avatars that failed to download shouldn't stay fallbacks for the rest of the game
+            let all_fetched = avatars.iter().all(Result::is_ok);
+
+            let fallbacks = players
+                .iter()
+                .map(|p| (p.watch_colour().map(str::to_string), p.room_number()))
+                .collect::<Vec<_>>();
+
+            let png = tokio::task::spawn_blocking(move || -> Result<Vec<u8>, Error> {
+                let avatars = avatars
+                    .into_iter()
+                    .zip(fallbacks)
+                    .map(|(avatar, (colour, room))| match avatar {
+                        Ok(Some(avatar)) => avatar,
+                        _ => compositor::fallback_avatar(colour.as_deref(), room),
+                    })
+                    .collect();
+                metrics::time_render("avatars", || compositor::render(avatars, &alivenesses))
+            })
+            .await??;
+
+            let png = Arc::new(png);
+            if all_fetched {
+                composites.insert(key, Arc::clone(&png));
+            }
+            png
         }
-        a
-    };
-
-    let merged_avatars = tokio::task::spawn_blocking(move || -> Result<AttachmentType, Error> {
-        info!("Grayscaling avatars...");
-        let avatars = grayscale_dead_players(avatars, &alivenesses);
-        info!("Merging avatars...");
-        let background_image = make_background_image(&alivenesses)?;
-        let merged_avatars = merge_avatars(avatars, background_image)?;
-        let merged_avatars_png = encode_to_png(merged_avatars)?;
-        Ok(AttachmentType::Bytes {
-            data: merged_avatars_png.into(),
-            filename: "avatars.png".into(),
-        })
-    })
-    .await??;
-
-    let cdn = {
-        info!("build_embed_for_target_choice: trying to lock data");
-        let data = ctx.data.read().await;
-        info!("build_embed_for_target_choice: Data locked");
-        *data.get::<Cdn>().expect("Where's my CDN")
     };
 
-    let msg = cdn
-        .send_message(ctx, |m| m.add_file(merged_avatars))
-        .await?;
-
     let mut embed = CreateEmbed::default();
     embed.title(embed_title);
-    embed.image(msg.attachments[0].url.clone());
+    embed.image(format!("attachment://{}", AVATARS_FILE_NAME));
 
-    Ok(embed)
+    Ok(TargetChoice { embed, avatars_png })
 }
 
-async fn fetch_avatars(ctx: &Context, players: &[UserId]) -> Result<Vec<Image>, Error> {
-    let data = ctx.data.read().await;
-    let reqwest = data.get::<ReqwestClient>().unwrap_or_else(|| {
-        error!("Reqwest client wasn't in ctx.data for some reason");
-        panic!();
-    });
+/// Fetches the avatars of `players`, or `None` for those who don't have one
+pub(super) async fn fetch_avatars(
+    ctx: &Context,
+    players: &[Player],
+) -> Vec<Result<Option<Image>, Error>> {
+    let (reqwest, cache) = {
+        let data = ctx.data.read().await;
+        let reqwest = data.get::<ReqwestClient>().cloned().unwrap_or_else(|| {
+            error!("Reqwest client wasn't in ctx.data for some reason");
+            panic!();
+        });
+        (reqwest, data.get::<AvatarCacheContainer>().cloned())
+    };
 
     let mut avatars = Vec::with_capacity(6);
 
     for player in players {
-        let user = player.to_user(ctx).await?;
-
-        let (image_url, image_format) = match user.static_avatar_url() {
-            Some(webp_url) => (webp_url, ImageFormat::WebP),
-            None => (user.default_avatar_url(), ImageFormat::Png),
-        };
-
-        let image_request = reqwest.get(&image_url).build()?;
-        let response = reqwest.execute(image_request).await?;
-        let raw_image = response.bytes().await?;
-
-        let image = match image_format {
-            // `image` chokes on webp's with alpha in them, so we `libwebp-image` in order to workaround that.
-            // This also gives us colour support, which `image` doesn't have
-            // Pretty cool overall, sadly that's a few more deps though
-            ImageFormat::WebP => webp_load_from_memory(&raw_image)?,
-            ImageFormat::Png => load_from_memory_with_format(&raw_image, ImageFormat::Png)?,
-            _ => unreachable!("This image format variable should only ever be Webp or Png"),
-        };
-
-        let image = image.into_rgba8();
-        avatars.push(image);
-    }
-
-    Ok(avatars)
-}
-
-fn grayscale_dead_players(mut avatars: Vec<Image>, alivenesses: &[bool]) -> Vec<Image> {
-    for (avatar, alive) in avatars.iter_mut().zip(alivenesses.iter()) {
-        if !alive {
-            *avatar = DynamicImage::ImageLuma8(colorops::grayscale(avatar)).to_rgba8();
+        let avatar = fetch_avatar(ctx, &reqwest, cache.as_deref(), player.id()).await;
+        if let Err(err) = &avatar {
+            warn!("Could not fetch the avatar of {}: {}", player.id(), err);
+            metrics::avatar_fetch_failed();
         }
+        avatars.push(avatar);
     }
 
     avatars
 }
 
-const IMAGE_WIDTH: u32 = 512;
-
-fn make_background_image(alivenesses: &[bool]) -> Result<Image, Error> {
-    let mut res = DynamicImage::new_rgba8(6 * IMAGE_WIDTH, 764).to_rgba8();
-    let mut offset = 0;
-
-    for i in 1..7 {
-        let img = if alivenesses[(i - 1) as usize] {
-            crate::resources::number_reactions(i)?
-        } else {
-            crate::resources::get_skull()?
-        };
+/// Gets the avatar of `user` from the cache, or downloads it if it's not cached
+///
+/// Returns `None` if the user has no avatar.
+async fn fetch_avatar(
+    ctx: &Context,
+    reqwest: &Reqwest,
+    cache: Option<&AvatarCache>,
+    user: UserId,
+) -> Result<Option<Image>, Error> {
+    let user = user.to_user(ctx).await?;
+    let (hash, url) = match (&user.avatar, user.static_avatar_url()) {
+        (Some(hash), Some(url)) => (hash, url),
+        _ => return Ok(None),
+    };
 
-        for (x, y, pixel) in img.enumerate_pixels() {
-            res.put_pixel(offset + x, y, *pixel);
+    if let Some(raw_image) = cache.and_then(|c| c.get(hash)) {
+        match compositor::decode_avatar(&raw_image) {
+            Ok(image) => return Ok(Some(image)),
+            Err(err) => warn!(
+                "Cached avatar {} is broken, downloading it again: {}",
+                hash, err
+            ),
         }
-
-        offset += IMAGE_WIDTH;
-    }
-
-    Ok(res)
-}
-
-/// This function creates an image big enough to contain the first 6 images in the vector,
-/// putting them one after each other after they got resized to be 512x512
-fn merge_avatars(mut avatars: Vec<Image>, mut image: Image) -> Result<Image, Error> {
-    avatars.truncate(6);
-
-    let mut resized_avatars = Vec::with_capacity(6);
-
-    for ava in &avatars {
-        resized_avatars.push(imageops::resize(
-            ava,
-            IMAGE_WIDTH,
-            IMAGE_WIDTH,
-            imageops::Nearest,
-        ));
     }
 
-    warn!(" w:{} h:{}", image.width(), image.height());
+    let raw_image = match download(reqwest, &url).await {
+        Ok(raw_image) => raw_image,
+        Err(err) => match cache.and_then(|c| c.get_stale(hash)) {
+            Some(raw_image) => {
+                warn!(
+                    "Could not download avatar {}, using an old copy: {}",
+                    hash, err
+                );
+                // Not cached again, so the old copy still ages out of the cache
+                return Ok(Some(compositor::decode_avatar(&raw_image)?));
+            }
+            None => return Err(err),
+        },
+    };
 
-    let mut offset = 0;
+    let image = compositor::decode_avatar(&raw_image)?;
 
-    for ava in &resized_avatars {
-        for (x, y, pixel) in ava.enumerate_pixels() {
-            image.put_pixel(x + offset, y, *pixel);
+    if let Some(cache) = cache {
+        if let Err(err) = cache.insert(hash, &raw_image) {
+            warn!("Could not cache avatar {}: {}", hash, err);
         }
-        offset += IMAGE_WIDTH;
     }
 
-    Ok(image)
+    Ok(Some(image))
 }
 
-fn encode_to_png(img: Image) -> Result<Vec<u8>, Error> {
-    let mut buffer = vec![];
-    let encoder = PngEncoder::new(&mut buffer);
-
-    encoder.encode(img.as_raw(), img.width(), img.height(), ColorType::Rgba8)?;
-
-    Ok(buffer)
+async fn download(reqwest: &Reqwest, url: &str) -> Result<Vec<u8>, Error> {
+    let request = reqwest.get(url).build()?;
+    let response = reqwest.execute(request).await?.error_for_status()?;
+    Ok(response.bytes().await?.to_vec())
 }
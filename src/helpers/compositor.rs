@@ -0,0 +1,254 @@
+//! The image pipeline behind target choice embeds
+//!
+//! Everything in here is synchronous and doesn't touch Discord, so it can be run on a blocking
+//! thread and benchmarked on its own (see `benches/image_pipeline.rs`).
+
+use crate::{helpers::Error, resources};
+
+use image::{
+    self, imageops, imageops::colorops, png::PngEncoder, ColorType, DynamicImage, ImageFormat, Rgba,
+};
+use libwebp_image::webp_load_from_memory;
+use tracing::{info, warn};
+
+pub type Image = image::RgbaImage;
+
+/// Renders the avatars of the players, in order, as a PNG strip with the reaction used to pick
+/// each one below them
+///
+/// The avatars of dead players get grayscaled and a skull is shown instead of their reaction.
+pub fn render(avatars: Vec<Image>, alivenesses: &[bool]) -> Result<Vec<u8>, Error> {
+    info!("Grayscaling avatars...");
+    let avatars = grayscale_dead_players(avatars, alivenesses);
+    info!("Merging avatars...");
+    let background_image = make_background_image(alivenesses)?;
+    let merged_avatars = merge_avatars(avatars, background_image)?;
+    encode_to_png(merged_avatars)
+}
+
+/// Decodes an avatar, which Discord serves as a WebP, though anything `image` can read works
+///
+/// Only the first frame of animated images is used.
+pub fn decode_avatar(raw_image: &[u8]) -> Result<Image, Error> {
+    let image = match image::guess_format(raw_image)? {
+        // `image` chokes on webp's with alpha in them, so we `libwebp-image` in order to workaround that.
+        // This also gives us colour support, which `image` doesn't have
+        // Pretty cool overall, sadly that's a few more deps though
+        ImageFormat::WebP => webp_load_from_memory(raw_image)?,
+        format => image::load_from_memory_with_format(raw_image, format)?,
+    };
+
+    Ok(image.into_rgba8())
+}
+
+/// Generates an avatar for players whose avatar couldn't be fetched, or who don't have one
+///
+/// It's the colour of the player's watch, with the number of their room in the middle.
+pub fn fallback_avatar(watch_colour: Option<&str>, room: Option<u8>) -> Image {
+    let mut avatar = Image::from_pixel(IMAGE_WIDTH, IMAGE_WIDTH, colour_of_watch(watch_colour));
+
+    if let Some(room) = room {
+        match resources::number_reactions(room) {
+            Ok(reaction) => {
+                let number = reaction_emoji(&reaction);
+                let y = (IMAGE_WIDTH - number.height()) / 2;
+                imageops::overlay(&mut avatar, &number, 0, y);
+            }
+            Err(err) => warn!("Could not load the number of room {}: {}", room, err),
+        }
+    }
+
+    avatar
+}
+
+/// Cuts the emoji out of one of the reaction images from `res/reactions`
+///
+/// The emoji is drawn in the part of those images that goes under the avatar.
+pub fn reaction_emoji(reaction: &Image) -> Image {
+    let emoji_height = reaction.height() - IMAGE_WIDTH;
+    imageops::crop_imm(reaction, 0, IMAGE_WIDTH, IMAGE_WIDTH, emoji_height).to_image()
+}
+
+pub fn colour_of_watch(watch_colour: Option<&str>) -> Rgba<u8> {
+    let [r, g, b] = match watch_colour {
+        Some("blue") => [0x2f, 0x5d, 0xa8],
+        Some("beige") => [0xd9, 0xc6, 0x9e],
+        Some("orange") => [0xe0, 0x7b, 0x24],
+        Some("green") => [0x3a, 0x8a, 0x46],
+        Some("black") => [0x20, 0x20, 0x20],
+        Some("red") => [0xb8, 0x2e, 0x2e],
+        _ => [0x80, 0x80, 0x80],
+    };
+    Rgba([r, g, b, 0xff])
+}
+
+pub fn grayscale_dead_players(mut avatars: Vec<Image>, alivenesses: &[bool]) -> Vec<Image> {
+    for (avatar, alive) in avatars.iter_mut().zip(alivenesses.iter()) {
+        if !alive {
+            *avatar = DynamicImage::ImageLuma8(colorops::grayscale(avatar)).to_rgba8();
+        }
+    }
+
+    avatars
+}
+
+/// The width (and height) every avatar gets resized to
+pub const IMAGE_WIDTH: u32 = 512;
+
+pub fn make_background_image(alivenesses: &[bool]) -> Result<Image, Error> {
+    let mut res = DynamicImage::new_rgba8(6 * IMAGE_WIDTH, 764).to_rgba8();
+    let mut offset = 0;
+
+    for i in 1..7 {
+        let img = if alivenesses[(i - 1) as usize] {
+            resources::number_reactions(i)?
+        } else {
+            resources::get_skull()?
+        };
+
+        for (x, y, pixel) in img.enumerate_pixels() {
+            res.put_pixel(offset + x, y, *pixel);
+        }
+
+        offset += IMAGE_WIDTH;
+    }
+
+    Ok(res)
+}
+
+/// This function creates an image big enough to contain the first 6 images in the vector,
+/// putting them one after each other after they got resized to be 512x512
+pub fn merge_avatars(mut avatars: Vec<Image>, mut image: Image) -> Result<Image, Error> {
+    avatars.truncate(6);
+
+    let mut resized_avatars = Vec::with_capacity(6);
+
+    for ava in &avatars {
+        resized_avatars.push(imageops::resize(
+            ava,
+            IMAGE_WIDTH,
+            IMAGE_WIDTH,
+            imageops::Nearest,
+        ));
+    }
+
+    warn!(" w:{} h:{}", image.width(), image.height());
+
+    let mut offset = 0;
+
+    for ava in &resized_avatars {
+        for (x, y, pixel) in ava.enumerate_pixels() {
+            image.put_pixel(x + offset, y, *pixel);
+        }
+        offset += IMAGE_WIDTH;
+    }
+
+    Ok(image)
+}
+
+pub fn encode_to_png(img: Image) -> Result<Vec<u8>, Error> {
+    let mut buffer = vec![];
+    let encoder = PngEncoder::new(&mut buffer);
+
+    encoder.encode(img.as_raw(), img.width(), img.height(), ColorType::Rgba8)?;
+
+    Ok(buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{env, fs, path::PathBuf};
+
+    fn fixture_path(path: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures")
+            .join(path)
+    }
+
+    fn avatar(name: &str) -> Image {
+        let raw_image = fs::read(fixture_path(&format!("avatars/{}", name))).unwrap();
+        decode_avatar(&raw_image).unwrap_or_else(|err| panic!("Could not decode {}: {}", name, err))
+    }
+
+    /// One avatar of every kind the pipeline gets fed
+    fn avatars() -> Vec<Image> {
+        vec![
+            avatar("square.png"),
+            avatar("wide.png"),
+            avatar("tall.png"),
+            avatar("lossless.webp"),
+            avatar("animated.gif"),
+            fallback_avatar(Some("red"), Some(6)),
+        ]
+    }
+
+    /// Checks that `png` looks exactly like the golden image `name`
+    ///
+    /// Run the tests with `UPDATE_GOLDEN=1` to write the golden images again after changing the
+    /// pipeline on purpose, and check the new images by eye before committing them.
+    fn assert_matches_golden(name: &str, png: &[u8]) {
+        let path = fixture_path(&format!("golden/{}", name));
+
+        if env::var_os("UPDATE_GOLDEN").is_some() {
+            fs::write(&path, png).unwrap();
+            return;
+        }
+
+        let golden =
+            fs::read(&path).unwrap_or_else(|err| panic!("Could not read {}: {}", name, err));
+        // The pixels are compared rather than the files, as PNG encoders are free to change how
+        // they compress images
+        let expected = decode_avatar(&golden).unwrap();
+        let actual = decode_avatar(png).unwrap();
+
+        assert_eq!(
+            expected.dimensions(),
+            actual.dimensions(),
+            "{} changed size",
+            name
+        );
+        assert!(
+            expected == actual,
+            "The image doesn't look like {} anymore",
+            name
+        );
+    }
+
+    #[test]
+    fn all_alive() {
+        let png = render(avatars(), &[true; 6]).unwrap();
+        assert_matches_golden("all_alive.png", &png);
+    }
+
+    #[test]
+    fn some_dead() {
+        let png = render(avatars(), &[true, false, true, false, false, true]).unwrap();
+        assert_matches_golden("some_dead.png", &png);
+    }
+
+    #[test]
+    fn all_dead() {
+        let png = render(avatars(), &[false; 6]).unwrap();
+        assert_matches_golden("all_dead.png", &png);
+    }
+
+    #[test]
+    fn animated_avatars_use_their_first_frame() {
+        let gif = avatar("animated.gif");
+        let first_frame = avatar("animated_first_frame.png");
+
+        assert!(gif == first_frame);
+    }
+
+    #[test]
+    fn fallback_avatars_are_the_colour_of_the_watch() {
+        for colour in &crate::game::item::WATCH_COLOURS {
+            let avatar = fallback_avatar(Some(colour), Some(1));
+
+            assert_eq!(avatar.dimensions(), (IMAGE_WIDTH, IMAGE_WIDTH));
+            assert_eq!(avatar.get_pixel(0, 0), &colour_of_watch(Some(colour)));
+            assert_ne!(colour_of_watch(Some(colour)), colour_of_watch(None));
+        }
+    }
+}
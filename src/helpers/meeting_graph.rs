@@ -0,0 +1,445 @@
+//! Who met whom in secret over the course of a game
+//!
+//! The graph can be exported as Graphviz DOT, for people who want to lay it out their own way, or
+//! rendered to a PNG directly so no Graphviz install is needed on the host.
+
+use super::{
+    bitmap_font::{draw_text, fill, text_width, GLYPH_HEIGHT},
+    compositor::{self, Image},
+    Error,
+};
+use crate::game::Player;
+
+use image::Rgba;
+use serenity::model::id::UserId;
+use std::{
+    collections::{BTreeMap, HashMap},
+    f32::consts::PI,
+    fmt::Write,
+};
+
+/// A player, as a node of the graph
+pub struct Node {
+    pub id: UserId,
+    pub name: String,
+    pub room: Option<u8>,
+    pub watch_colour: Option<String>,
+    pub alive: bool,
+}
+
+/// A secret meeting `from` asked `to` for
+pub struct Edge {
+    pub from: UserId,
+    pub to: UserId,
+    /// Counted from 0, like everywhere else in the game
+    pub day: u8,
+    /// Which meeting of the day it was, 1 or 2
+    pub meeting: u8,
+}
+
+pub struct MeetingGraph {
+    nodes: Vec<Node>,
+    edges: Vec<Edge>,
+}
+
+impl MeetingGraph {
+    /// Builds the graph of the secret meetings of `players`, `names` being how each of them gets
+    /// labelled in the DOT export
+    pub fn new(players: &BTreeMap<UserId, Player>, names: &HashMap<UserId, String>) -> Self {
+        let nodes = players
+            .values()
+            .map(|player| Node {
+                id: player.id(),
+                name: names
+                    .get(&player.id())
+                    .cloned()
+                    .unwrap_or_else(|| player.id().to_string()),
+                room: player.room_number(),
+                watch_colour: player.watch_colour().map(str::to_string),
+                alive: player.is_alive(),
+            })
+            .collect();
+
+        let edges = players
+            .values()
+            .flat_map(|player| {
+                player
+                    .secret_meetings()
+                    .map(move |(day, meeting, partner)| Edge {
+                        from: player.id(),
+                        to: partner,
+                        day,
+                        meeting,
+                    })
+            })
+            .collect();
+
+        Self::from_parts(nodes, edges)
+    }
+
+    pub fn from_parts(nodes: Vec<Node>, mut edges: Vec<Edge>) -> Self {
+        edges.sort_by_key(|edge| (edge.day, edge.meeting, edge.from, edge.to));
+        Self { nodes, edges }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.edges.is_empty()
+    }
+
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::new();
+
+        // Writing to a String can't fail, so the results are ignored
+        let _ = writeln!(dot, "digraph secret_meetings {{");
+        let _ = writeln!(
+            dot,
+            "    node [shape=circle, style=filled, fontname=\"sans-serif\"];"
+        );
+        let _ = writeln!(dot, "    edge [fontname=\"sans-serif\"];");
+
+        for node in &self.nodes {
+            let colour = compositor::colour_of_watch(node.watch_colour.as_deref());
+            let mut label = match node.room {
+                Some(room) => format!("Room {}\\n{}", room, escape(&node.name)),
+                None => escape(&node.name),
+            };
+            if !node.alive {
+                label.push_str("\\n(dead)");
+            }
+
+            let _ = writeln!(
+                dot,
+                "    \"{}\" [label=\"{}\", fillcolor=\"{}\", fontcolor=\"{}\"{}];",
+                node.id,
+                label,
+                hex(colour),
+                hex(text_colour_on(colour)),
+                if node.alive {
+                    ""
+                } else {
+                    ", style=\"filled,dashed\""
+                },
+            );
+        }
+
+        for edge in &self.edges {
+            let _ = writeln!(
+                dot,
+                "    \"{}\" -> \"{}\" [label=\"Day {}, meeting {}\", color=\"{}\"];",
+                edge.from,
+                edge.to,
+                edge.day + 1,
+                edge.meeting,
+                hex(day_colour(edge.day)),
+            );
+        }
+
+        let _ = writeln!(dot, "}}");
+        dot
+    }
+
+    /// Renders the graph as a PNG, with the players in a circle in the order of their rooms
+    pub fn render(&self) -> Result<Vec<u8>, Error> {
+        let mut image = Image::from_pixel(SIZE, SIZE, BACKGROUND);
+        let centre = SIZE as f32 / 2.0;
+
+        let mut nodes = self.nodes.iter().collect::<Vec<_>>();
+        nodes.sort_by_key(|node| (node.room, node.id));
+        let positions = nodes
+            .iter()
+            .enumerate()
+            .map(|(idx, node)| {
+                let angle = -PI / 2.0 + idx as f32 * 2.0 * PI / nodes.len() as f32;
+                let position = (
+                    centre + LAYOUT_RADIUS * angle.cos(),
+                    centre + LAYOUT_RADIUS * angle.sin(),
+                );
+                (node.id, position)
+            })
+            .collect::<HashMap<_, _>>();
+
+        // Meetings between the same two players are spread out so they don't overlap
+        let mut pairs: BTreeMap<(UserId, UserId), Vec<&Edge>> = BTreeMap::new();
+        for edge in &self.edges {
+            let pair = (edge.from.min(edge.to), edge.from.max(edge.to));
+            pairs.entry(pair).or_default().push(edge);
+        }
+
+        let mut labels = vec![];
+        for ((a, b), edges) in pairs {
+            let (a, b) = match (positions.get(&a), positions.get(&b)) {
+                (Some(a), Some(b)) => (*a, *b),
+                _ => continue,
+            };
+            let length = ((b.0 - a.0).powi(2) + (b.1 - a.1).powi(2)).sqrt();
+            if length == 0.0 {
+                continue;
+            }
+            let normal = (-(b.1 - a.1) / length, (b.0 - a.0) / length);
+
+            for (idx, edge) in edges.iter().enumerate() {
+                let shift = (idx as f32 - (edges.len() - 1) as f32 / 2.0) * EDGE_SPACING;
+                let shifted = |p: (f32, f32)| (p.0 + normal.0 * shift, p.1 + normal.1 * shift);
+                let (from, to) = if positions[&edge.from] == a {
+                    (shifted(a), shifted(b))
+                } else {
+                    (shifted(b), shifted(a))
+                };
+
+                let direction = ((to.0 - from.0) / length, (to.1 - from.1) / length);
+                let start = (
+                    from.0 + direction.0 * NODE_RADIUS,
+                    from.1 + direction.1 * NODE_RADIUS,
+                );
+                let tip = (
+                    to.0 - direction.0 * NODE_RADIUS,
+                    to.1 - direction.1 * NODE_RADIUS,
+                );
+
+                let colour = day_colour(edge.day);
+                draw_line(&mut image, start, tip, colour);
+                draw_arrowhead(&mut image, tip, direction, colour);
+
+                // Labels of parallel meetings are staggered along the line, or they'd cover each other
+                let along = (length - 2.0 * NODE_RADIUS) * LABEL_SPREAD * shift
+                    / (edges.len() as f32 * EDGE_SPACING);
+                labels.push((
+                    format!("D{}#{}", edge.day + 1, edge.meeting),
+                    (
+                        (start.0 + tip.0) / 2.0 + normal.1 * along,
+                        (start.1 + tip.1) / 2.0 - normal.0 * along,
+                    ),
+                    (normal.1, -normal.0),
+                    colour,
+                ));
+            }
+        }
+
+        for node in nodes {
+            let (x, y) = positions[&node.id];
+            let colour = compositor::colour_of_watch(node.watch_colour.as_deref());
+            if node.alive {
+                draw_circle(&mut image, (x, y), NODE_RADIUS, colour);
+            } else {
+                // Dead players keep a ring of their watch's colour
+                draw_circle(&mut image, (x, y), NODE_RADIUS, colour);
+                draw_circle(&mut image, (x, y), NODE_RADIUS - 8.0, DEAD_FILL);
+            }
+
+            if let Some(room) = node.room {
+                let text = room.to_string();
+                let text_colour = if node.alive {
+                    text_colour_on(colour)
+                } else {
+                    text_colour_on(DEAD_FILL)
+                };
+                draw_centred_text(&mut image, &text, (x, y), NODE_TEXT_SCALE, text_colour);
+            }
+        }
+
+        // Labels go on top of everything else so they stay readable, moved along their line when
+        // they'd cover one that's already there
+        let mut placed: Vec<(u32, u32, u32, u32)> = vec![];
+        for (text, position, axis, colour) in labels {
+            let width = text_width(&text, LABEL_SCALE) + 2 * LABEL_PADDING;
+            let height = GLYPH_HEIGHT * LABEL_SCALE + 2 * LABEL_PADDING;
+            let place = |nudge: f32| {
+                let x = position.0 + axis.0 * nudge - width as f32 / 2.0;
+                let y = position.1 + axis.1 * nudge - height as f32 / 2.0;
+                (x.max(0.0) as u32, y.max(0.0) as u32, width, height)
+            };
+            let overlaps = |a: &(u32, u32, u32, u32), b: &(u32, u32, u32, u32)| {
+                a.0 < b.0 + b.2 && b.0 < a.0 + a.2 && a.1 < b.1 + b.3 && b.1 < a.1 + a.3
+            };
+
+            let rect = [0.0, 1.0, -1.0, 2.0, -2.0]
+                .iter()
+                .map(|step| place(step * LABEL_NUDGE))
+                .find(|rect| !placed.iter().any(|other| overlaps(rect, other)))
+                .unwrap_or_else(|| place(0.0));
+            placed.push(rect);
+
+            let (x, y, _, _) = rect;
+            fill(&mut image, x, y, width, height, BACKGROUND);
+            draw_text(
+                &mut image,
+                &text,
+                x + LABEL_PADDING,
+                y + LABEL_PADDING,
+                LABEL_SCALE,
+                colour,
+            );
+        }
+
+        compositor::encode_to_png(image)
+    }
+}
+
+const SIZE: u32 = 960;
+const LAYOUT_RADIUS: f32 = 340.0;
+const NODE_RADIUS: f32 = 60.0;
+const EDGE_SPACING: f32 = 28.0;
+/// How much of a line the labels of the meetings between two players are spread over
+const LABEL_SPREAD: f32 = 0.8;
+const EDGE_THICKNESS: f32 = 4.0;
+const ARROWHEAD_LENGTH: f32 = 22.0;
+const NODE_TEXT_SCALE: u32 = 8;
+const LABEL_SCALE: u32 = 3;
+const LABEL_NUDGE: f32 = 36.0;
+const LABEL_PADDING: u32 = 4;
+
+const BACKGROUND: Rgba<u8> = Rgba([0x23, 0x27, 0x2a, 0xff]);
+const DEAD_FILL: Rgba<u8> = Rgba([0x4f, 0x54, 0x5c, 0xff]);
+
+/// The colour of the meetings of `day`, so the days can be told apart
+fn day_colour(day: u8) -> Rgba<u8> {
+    const COLOURS: [[u8; 3]; 6] = [
+        [0xf2, 0xf2, 0xf2],
+        [0xf5, 0xc2, 0x1b],
+        [0x4d, 0xd0, 0xe1],
+        [0xf0, 0x62, 0x92],
+        [0x9c, 0xcc, 0x65],
+        [0xba, 0x68, 0xc8],
+    ];
+    let [r, g, b] = COLOURS[day as usize % COLOURS.len()];
+    Rgba([r, g, b, 0xff])
+}
+
+/// Black or white, whichever is easier to read on `background`
+fn text_colour_on(background: Rgba<u8>) -> Rgba<u8> {
+    let [r, g, b, _] = background.0;
+    let luma = (299 * r as u32 + 587 * g as u32 + 114 * b as u32) / 1000;
+    if luma > 150 {
+        Rgba([0x10, 0x10, 0x10, 0xff])
+    } else {
+        Rgba([0xff, 0xff, 0xff, 0xff])
+    }
+}
+
+fn hex(colour: Rgba<u8>) -> String {
+    let [r, g, b, _] = colour.0;
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+/// Escapes `text` for use inside a quoted DOT string
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn draw_centred_text(
+    image: &mut Image,
+    text: &str,
+    centre: (f32, f32),
+    scale: u32,
+    colour: Rgba<u8>,
+) {
+    let width = text_width(text, scale) as f32;
+    let height = (GLYPH_HEIGHT * scale) as f32;
+    let x = (centre.0 - width / 2.0).max(0.0) as u32;
+    let y = (centre.1 - height / 2.0).max(0.0) as u32;
+    draw_text(image, text, x, y, scale, colour);
+}
+
+fn draw_circle(image: &mut Image, centre: (f32, f32), radius: f32, colour: Rgba<u8>) {
+    let left = (centre.0 - radius).max(0.0) as u32;
+    let top = (centre.1 - radius).max(0.0) as u32;
+    let right = ((centre.0 + radius).ceil() as u32).min(image.width() - 1);
+    let bottom = ((centre.1 + radius).ceil() as u32).min(image.height() - 1);
+
+    for y in top..=bottom {
+        for x in left..=right {
+            let (dx, dy) = (x as f32 - centre.0, y as f32 - centre.1);
+            if dx * dx + dy * dy <= radius * radius {
+                image.put_pixel(x, y, colour);
+            }
+        }
+    }
+}
+
+fn draw_line(image: &mut Image, from: (f32, f32), to: (f32, f32), colour: Rgba<u8>) {
+    let length = ((to.0 - from.0).powi(2) + (to.1 - from.1).powi(2)).sqrt();
+    let steps = length.ceil() as u32;
+    let half = EDGE_THICKNESS / 2.0;
+
+    for step in 0..=steps {
+        let t = if steps == 0 {
+            0.0
+        } else {
+            step as f32 / steps as f32
+        };
+        let x = from.0 + (to.0 - from.0) * t;
+        let y = from.1 + (to.1 - from.1) * t;
+        fill(
+            image,
+            (x - half).max(0.0) as u32,
+            (y - half).max(0.0) as u32,
+            EDGE_THICKNESS as u32,
+            EDGE_THICKNESS as u32,
+            colour,
+        );
+    }
+}
+
+fn draw_arrowhead(image: &mut Image, tip: (f32, f32), direction: (f32, f32), colour: Rgba<u8>) {
+    let (sin, cos) = (PI / 7.0).sin_cos();
+    for side in &[-1.0, 1.0] {
+        // The direction rotated back towards the line's start, to either side
+        let back = (
+            -(direction.0 * cos - side * direction.1 * sin),
+            -(side * direction.0 * sin + direction.1 * cos),
+        );
+        let end = (
+            tip.0 + back.0 * ARROWHEAD_LENGTH,
+            tip.1 + back.1 * ARROWHEAD_LENGTH,
+        );
+        draw_line(image, tip, end, colour);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(id: u64, room: u8) -> Node {
+        Node {
+            id: UserId(id),
+            name: format!("Player \"{}\"", room),
+            room: Some(room),
+            watch_colour: Some(crate::game::item::WATCH_COLOURS[room as usize - 1].to_string()),
+            alive: room != 2,
+        }
+    }
+
+    fn edge(from: u64, to: u64, day: u8, meeting: u8) -> Edge {
+        Edge {
+            from: UserId(from),
+            to: UserId(to),
+            day,
+            meeting,
+        }
+    }
+
+    #[test]
+    fn dot_has_a_node_per_player_and_an_edge_per_meeting() {
+        let graph = MeetingGraph::from_parts(
+            vec![node(10, 1), node(20, 2), node(30, 3)],
+            vec![edge(10, 20, 0, 1), edge(20, 10, 0, 2), edge(30, 10, 1, 1)],
+        );
+        let dot = graph.to_dot();
+
+        assert!(dot.starts_with("digraph secret_meetings {"));
+        assert!(dot.contains(r##""10" [label="Room 1\nPlayer \"1\"", fillcolor="#2f5da8""##));
+        assert!(dot.contains(r#"\n(dead)", "#));
+        assert!(dot.contains(r#""10" -> "20" [label="Day 1, meeting 1""#));
+        assert!(dot.contains(r#""20" -> "10" [label="Day 1, meeting 2""#));
+        assert!(dot.contains(r#""30" -> "10" [label="Day 2, meeting 1""#));
+        assert_eq!(dot.matches(" -> ").count(), 3);
+    }
+
+    #[test]
+    fn renders_without_meetings() {
+        let graph = MeetingGraph::from_parts(vec![node(10, 1), node(20, 2)], vec![]);
+        assert!(graph.is_empty());
+        assert!(!graph.render().unwrap().is_empty());
+    }
+}
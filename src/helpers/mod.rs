@@ -1,6 +1,12 @@
+pub mod bitmap_font;
 pub mod choose_target;
+pub mod compositor;
 pub mod confirm_murder;
+pub mod meeting_graph;
 pub mod perms;
+pub mod preflight;
 pub mod react;
+pub mod start_time;
+pub mod status_board;
 
 pub type Error = Box<dyn std::error::Error + Send + Sync>;
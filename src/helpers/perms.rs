@@ -1,8 +1,24 @@
-use serenity::model::{
-    channel::{PermissionOverwrite, PermissionOverwriteType},
-    prelude::*,
+use serenity::{
+    client::Context,
+    framework::standard::CommandResult,
+    model::{
+        channel::{PermissionOverwrite, PermissionOverwriteType},
+        prelude::*,
+    },
 };
 
+/// Permissions the bot needs in a server in order to run games there
+pub fn required_bot_permissions() -> Permissions {
+    Permissions::MANAGE_ROLES
+        | Permissions::MANAGE_CHANNELS
+        | Permissions::READ_MESSAGES
+        | Permissions::SEND_MESSAGES
+        | Permissions::EMBED_LINKS
+        | Permissions::ATTACH_FILES
+        | Permissions::READ_MESSAGE_HISTORY
+        | Permissions::ADD_REACTIONS
+}
+
 pub fn make_allowed_override_for_user(
     user: UserId,
     deny_arbitrary_reactions: bool,
@@ -49,3 +65,69 @@ pub fn make_denied_override_for_role(role: RoleId) -> PermissionOverwrite {
         kind: PermissionOverwriteType::Role(role),
     }
 }
+
+pub fn make_read_only_override_for_role(role: RoleId) -> PermissionOverwrite {
+    PermissionOverwrite {
+        allow: Permissions::READ_MESSAGES | Permissions::READ_MESSAGE_HISTORY,
+        deny: Permissions::SEND_MESSAGES | Permissions::ADD_REACTIONS,
+        kind: PermissionOverwriteType::Role(role),
+    }
+}
+
+/// Whether `user` owns `guild` or is an administrator there, which lets them see every room
+pub async fn sees_every_room(ctx: &Context, guild: GuildId, user: UserId) -> CommandResult<bool> {
+    let is_owner = guild
+        .to_guild_cached(ctx)
+        .await
+        .map_or(false, |guild| guild.owner_id == user);
+    if is_owner {
+        return Ok(true);
+    }
+
+    Ok(guild
+        .member(ctx, user)
+        .await?
+        .permissions(ctx)
+        .await?
+        .administrator())
+}
+
+/// Gives `to` the permission overwrite `from` has in `channel`, and takes it away from `from`
+///
+/// Returns whether `from` had one to begin with.
+pub async fn move_member_overwrite(
+    ctx: &Context,
+    channel: ChannelId,
+    from: UserId,
+    to: UserId,
+) -> CommandResult<bool> {
+    let channel = match channel.to_channel(ctx).await?.guild() {
+        Some(channel) => channel,
+        None => return Ok(false),
+    };
+    let overwrite = channel
+        .permission_overwrites
+        .iter()
+        .find(|overwrite| overwrite.kind == PermissionOverwriteType::Member(from))
+        .cloned();
+
+    match overwrite {
+        Some(overwrite) => {
+            channel
+                .create_permission(
+                    ctx,
+                    &PermissionOverwrite {
+                        allow: overwrite.allow,
+                        deny: overwrite.deny,
+                        kind: PermissionOverwriteType::Member(to),
+                    },
+                )
+                .await?;
+            channel
+                .delete_permission(ctx, PermissionOverwriteType::Member(from))
+                .await?;
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
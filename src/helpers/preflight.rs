@@ -0,0 +1,219 @@
+//! Checks whether a game can be started before anything gets created for it
+
+use super::{perms, Error};
+use crate::{game::Game, i18n, storage::GuildConfig};
+
+use serenity::{
+    model::{
+        channel::ChannelType,
+        id::{ChannelId, GuildId, RoleId, UserId},
+        misc::Mentionable,
+        Permissions,
+    },
+    prelude::*,
+};
+
+/// Discord's limit on the amount of channels in a server, categories included
+const MAX_CHANNELS_IN_GUILD: usize = 500;
+/// Discord's limit on the amount of channels in a category
+const MAX_CHANNELS_IN_CATEGORY: usize = 50;
+
+/// Everything about a (future) game that's checked
+pub struct GameSetup {
+    pub guild: GuildId,
+    pub player_role: Option<RoleId>,
+    pub meeting_room: Option<ChannelId>,
+    pub announcement_channel: Option<ChannelId>,
+    pub rooms_category: Option<ChannelId>,
+    pub players: Vec<UserId>,
+}
+
+impl GameSetup {
+    pub fn from_game(game: &Game) -> Self {
+        Self {
+            guild: game.guild(),
+            player_role: Some(game.player_role()),
+            meeting_room: Some(game.meeting_room()),
+            announcement_channel: Some(game.announcement_channel()),
+            rooms_category: game.rooms_category(),
+            players: game
+                .joined_users()
+                .cloned()
+                .or_else(|| game.players().map(|p| p.keys().copied().collect()))
+                .unwrap_or_default(),
+        }
+    }
+
+    pub fn from_config(guild: GuildId, config: &GuildConfig) -> Self {
+        Self {
+            guild,
+            player_role: config.player_role,
+            meeting_room: config.meeting_room,
+            announcement_channel: config.announcement_channel,
+            rooms_category: config.rooms_category,
+            players: vec![],
+        }
+    }
+}
+
+/// Runs every check, returning a description of each problem found in `locale`
+///
+/// An empty result means that a game can be started with this setup.
+pub async fn check(ctx: &Context, setup: &GameSetup, locale: &str) -> Result<Vec<String>, Error> {
+    let mut problems = vec![];
+
+    let guild = match setup.guild.to_guild_cached(ctx).await {
+        Some(guild) => guild,
+        None => {
+            problems.push(i18n::tr(locale, "preflight.no_guild"));
+            return Ok(problems);
+        }
+    };
+
+    let bot_id = ctx.cache.current_user_id().await;
+    let me = match guild.member(ctx, bot_id).await {
+        Ok(me) => Some(me),
+        Err(err) => {
+            problems.push(i18n::tr_with(
+                locale,
+                "preflight.no_member",
+                &[("error", &err)],
+            ));
+            None
+        }
+    };
+
+    if let Some(me) = &me {
+        match me.permissions(ctx).await {
+            Ok(permissions) => {
+                let missing_permissions = perms::required_bot_permissions() - permissions;
+                if !missing_permissions.is_empty() {
+                    problems.push(i18n::tr_with(
+                        locale,
+                        "preflight.missing_permissions",
+                        &[("permissions", &format!("{:?}", missing_permissions))],
+                    ));
+                }
+            }
+            Err(err) => problems.push(i18n::tr_with(
+                locale,
+                "preflight.no_permissions",
+                &[("error", &err)],
+            )),
+        }
+    }
+
+    match setup.player_role.and_then(|id| guild.roles.get(&id)) {
+        Some(role) => {
+            let my_position = match &me {
+                Some(me) => me
+                    .highest_role_info(ctx)
+                    .await
+                    .map(|(_, position)| position)
+                    .unwrap_or(0),
+                // Already reported above
+                None => i64::MAX,
+            };
+            if my_position <= role.position {
+                problems.push(i18n::tr_with(
+                    locale,
+                    "preflight.role_too_low",
+                    &[("role", &role.name)],
+                ));
+            }
+        }
+        None => problems.push(i18n::tr(locale, "preflight.no_player_role")),
+    }
+
+    for (channel, what) in [
+        (setup.meeting_room, "preflight.meeting_room"),
+        (setup.announcement_channel, "preflight.announcement_channel"),
+    ]
+    .iter()
+    {
+        let what = i18n::tr(locale, what);
+        match channel.and_then(|id| guild.channels.get(&id)) {
+            Some(channel) => match channel.permissions_for_user(ctx, bot_id).await {
+                Ok(permissions) => {
+                    let needed = Permissions::READ_MESSAGES
+                        | Permissions::SEND_MESSAGES
+                        | Permissions::EMBED_LINKS;
+                    if !permissions.contains(needed) {
+                        problems.push(i18n::tr_with(
+                            locale,
+                            "preflight.channel_permissions",
+                            &[("channel", &what), ("name", &channel.name)],
+                        ));
+                    }
+                }
+                Err(err) => problems.push(i18n::tr_with(
+                    locale,
+                    "preflight.no_channel_permissions",
+                    &[("channel", &what), ("name", &channel.name), ("error", &err)],
+                )),
+            },
+            None => problems.push(i18n::tr_with(
+                locale,
+                "preflight.no_channel",
+                &[("channel", &what)],
+            )),
+        }
+    }
+
+    // Every game makes a category and a room for each player, and each day another category and
+    // a room for every secret meeting
+    let rooms_needed = if setup.rooms_category.is_some() { 6 } else { 7 };
+    let channels_needed = rooms_needed + 7;
+    if guild.channels.len() + channels_needed > MAX_CHANNELS_IN_GUILD {
+        problems.push(i18n::tr_with(
+            locale,
+            "preflight.too_many_channels",
+            &[
+                ("count", &guild.channels.len()),
+                ("needed", &channels_needed),
+                ("limit", &MAX_CHANNELS_IN_GUILD),
+            ],
+        ));
+    }
+
+    if let Some(category) = setup.rooms_category {
+        match guild.channels.get(&category) {
+            Some(c) if c.kind == ChannelType::Category => {
+                let children = guild
+                    .channels
+                    .values()
+                    .filter(|c| c.category_id == Some(category))
+                    .count();
+                if children + 6 > MAX_CHANNELS_IN_CATEGORY {
+                    problems.push(i18n::tr_with(
+                        locale,
+                        "preflight.category_full",
+                        &[("category", &c.name)],
+                    ));
+                }
+            }
+            _ => problems.push(i18n::tr(locale, "preflight.no_category")),
+        }
+    }
+
+    for player in &setup.players {
+        if guild.member(ctx, *player).await.is_err() {
+            problems.push(i18n::tr_with(
+                locale,
+                "preflight.player_left",
+                &[("player", &player.mention())],
+            ));
+        }
+    }
+
+    Ok(problems)
+}
+
+/// Formats the problems found by [`check`] as a bulleted list
+pub fn describe(problems: &[String]) -> String {
+    problems
+        .iter()
+        .map(|p| format!("• {}", p))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
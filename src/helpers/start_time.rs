@@ -0,0 +1,95 @@
+//! Reads the time a game should start at, as it's written in `!schedule`
+
+use chrono::{DateTime, Duration, NaiveDateTime, TimeZone, Utc};
+
+/// Formats for times without an offset, which are taken to be in UTC
+static UTC_FORMATS: [&str; 2] = ["%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M"];
+
+/// Reads `text` as either a time from now, like `90m`, `2h` or `1d12h`, or a date and time, like
+/// `2021-06-05T18:00+02:00` or `2021-06-05 16:00` in UTC
+pub fn parse(text: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let text = text.trim();
+
+    if let Some(duration) = parse_duration(text) {
+        return now.checked_add_signed(duration);
+    }
+
+    if let Ok(time) = DateTime::parse_from_rfc3339(text) {
+        return Some(time.with_timezone(&Utc));
+    }
+    if let Ok(time) = DateTime::parse_from_str(text, "%Y-%m-%dT%H:%M%:z") {
+        return Some(time.with_timezone(&Utc));
+    }
+
+    UTC_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+        .map(|time| Utc.from_utc_datetime(&time))
+}
+
+/// Reads durations made of a number followed by `d`, `h` or `m`, any number of times
+fn parse_duration(text: &str) -> Option<Duration> {
+    let mut total = Duration::zero();
+    let mut number = String::new();
+
+    for c in text.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+
+        let amount = number.parse::<i64>().ok()?;
+        number.clear();
+        let minutes = match c.to_ascii_lowercase() {
+            'd' => amount.checked_mul(24 * 60)?,
+            'h' => amount.checked_mul(60)?,
+            'm' => amount,
+            _ => return None,
+        };
+        // Keeps `Duration::minutes` from panicking, no game is scheduled that far off anyway
+        if minutes > 10 * 365 * 24 * 60 {
+            return None;
+        }
+        total = total.checked_add(&Duration::minutes(minutes))?;
+    }
+
+    if number.is_empty() && total > Duration::zero() {
+        Some(total)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn now() -> DateTime<Utc> {
+        Utc.ymd(2021, 6, 5).and_hms(12, 0, 0)
+    }
+
+    #[test]
+    fn reads_times_from_now() {
+        assert_eq!(
+            parse("90m", now()),
+            Some(Utc.ymd(2021, 6, 5).and_hms(13, 30, 0))
+        );
+        assert_eq!(
+            parse("1d2h", now()),
+            Some(Utc.ymd(2021, 6, 6).and_hms(14, 0, 0))
+        );
+        assert_eq!(parse("0m", now()), None);
+        assert_eq!(parse("15", now()), None);
+        assert_eq!(parse("3w", now()), None);
+    }
+
+    #[test]
+    fn reads_dates_and_times() {
+        let expected = Some(Utc.ymd(2021, 6, 5).and_hms(16, 0, 0));
+        assert_eq!(parse("2021-06-05 16:00", now()), expected);
+        assert_eq!(parse("2021-06-05T16:00", now()), expected);
+        assert_eq!(parse("2021-06-05T18:00+02:00", now()), expected);
+        assert_eq!(parse("2021-06-05T18:00:00+02:00", now()), expected);
+        assert_eq!(parse("tomorrow", now()), None);
+    }
+}
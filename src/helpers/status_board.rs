@@ -0,0 +1,186 @@
+//! A picture of the state of a game, attached to `!gameinfo` and to the announcements of new time
+//! blocks when a server turns it on
+
+use super::{
+    bitmap_font::{draw_text, fill, GLYPH_HEIGHT},
+    choose_target::fetch_avatars,
+    compositor::{self, Image},
+    Error,
+};
+use crate::{game::Game, metrics, resources};
+
+use image::{imageops, imageops::colorops, DynamicImage, Rgba};
+use serenity::{http::AttachmentType, prelude::*};
+use std::borrow::Cow;
+use tracing::{info, warn};
+
+/// The file name status boards are attached to messages with
+pub const FILE_NAME: &str = "status.png";
+
+/// A rendered status board, see [`build`]
+pub struct StatusBoard {
+    png: Vec<u8>,
+}
+
+impl StatusBoard {
+    pub fn attachment(&self) -> AttachmentType<'_> {
+        AttachmentType::Bytes {
+            data: Cow::Borrowed(&self.png),
+            filename: FILE_NAME.to_string(),
+        }
+    }
+}
+
+/// Renders the status board of `game`, or returns `None` if the game hasn't started yet
+pub async fn build(ctx: &Context, game: &Game) -> Result<Option<StatusBoard>, Error> {
+    let players = match game.players() {
+        Some(players) => players.values().cloned().collect::<Vec<_>>(),
+        None => return Ok(None),
+    };
+
+    let header = match (game.day(), game.time_range()) {
+        // Days are counted from 0 internally
+        (Some(day), Some(time_range)) => {
+            format!("Day {} {} {}", day + 1, game.state_name(), time_range)
+        }
+        _ => game.state_name().to_string(),
+    };
+    let reveal_roles = game.is_ended();
+
+    info!("Fetching avatars for the status board...");
+    let avatars = fetch_avatars(ctx, &players).await;
+
+    let cards = players
+        .iter()
+        .zip(avatars)
+        .map(|(player, avatar)| {
+            let watch_colour = player.watch_colour().map(str::to_string);
+            let room = player.room_number();
+            Card {
+                avatar: avatar.ok().flatten(),
+                room,
+                watch_colour,
+                alive: player.is_alive(),
+                role: if reveal_roles {
+                    Some(player.role_name().key())
+                } else {
+                    None
+                },
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let png = tokio::task::spawn_blocking(move || {
+        metrics::time_render("status_board", || render(&header, cards))
+    })
+    .await??;
+
+    Ok(Some(StatusBoard { png }))
+}
+
+/// What the status board shows about a player
+pub struct Card {
+    /// `None` when the avatar has to be generated
+    pub avatar: Option<Image>,
+    pub room: Option<u8>,
+    pub watch_colour: Option<String>,
+    pub alive: bool,
+    /// The role, which is only revealed once the game ends
+    pub role: Option<&'static str>,
+}
+
+const MARGIN: u32 = 16;
+const CARD_WIDTH: u32 = 256;
+const HEADER_HEIGHT: u32 = 80;
+const WATCH_BAND_HEIGHT: u32 = 24;
+/// The reaction images' emojis are scaled down to the width of a card
+const EMOJI_HEIGHT: u32 = 126;
+const ROLE_ICON_SIZE: u32 = 128;
+const TEXT_SCALE: u32 = 6;
+
+const BACKGROUND: Rgba<u8> = Rgba([0x23, 0x27, 0x2a, 0xff]);
+const HEADER_BACKGROUND: Rgba<u8> = Rgba([0x2c, 0x2f, 0x33, 0xff]);
+const TEXT_COLOUR: Rgba<u8> = Rgba([0xff, 0xff, 0xff, 0xff]);
+
+/// Renders the status board as a PNG
+///
+/// `header` is written at the top in capitals, with the cards of the players below it, in order.
+pub fn render(header: &str, cards: Vec<Card>) -> Result<Vec<u8>, Error> {
+    let reveal_roles = cards.iter().any(|card| card.role.is_some());
+    let card_count = cards.len().max(1) as u32;
+
+    let width = MARGIN + card_count * (CARD_WIDTH + MARGIN);
+    let mut height =
+        HEADER_HEIGHT + MARGIN + WATCH_BAND_HEIGHT + CARD_WIDTH + EMOJI_HEIGHT + MARGIN;
+    if reveal_roles {
+        height += ROLE_ICON_SIZE + MARGIN;
+    }
+
+    let mut board = Image::from_pixel(width, height, BACKGROUND);
+    fill(&mut board, 0, 0, width, HEADER_HEIGHT, HEADER_BACKGROUND);
+    draw_text(
+        &mut board,
+        header,
+        MARGIN,
+        (HEADER_HEIGHT - GLYPH_HEIGHT * TEXT_SCALE) / 2,
+        TEXT_SCALE,
+        TEXT_COLOUR,
+    );
+
+    let skull = emoji(resources::get_skull()?);
+
+    for (idx, card) in cards.into_iter().enumerate() {
+        let x = MARGIN + idx as u32 * (CARD_WIDTH + MARGIN);
+        let mut y = HEADER_HEIGHT + MARGIN;
+
+        let watch_colour = compositor::colour_of_watch(card.watch_colour.as_deref());
+        fill(
+            &mut board,
+            x,
+            y,
+            CARD_WIDTH,
+            WATCH_BAND_HEIGHT,
+            watch_colour,
+        );
+        y += WATCH_BAND_HEIGHT;
+
+        let avatar = match card.avatar {
+            Some(avatar) => avatar,
+            None => compositor::fallback_avatar(card.watch_colour.as_deref(), card.room),
+        };
+        let mut avatar = imageops::resize(&avatar, CARD_WIDTH, CARD_WIDTH, imageops::Nearest);
+        if !card.alive {
+            avatar = DynamicImage::ImageLuma8(colorops::grayscale(&avatar)).to_rgba8();
+            imageops::overlay(&mut avatar, &skull, 0, (CARD_WIDTH - EMOJI_HEIGHT) / 2);
+        }
+        imageops::overlay(&mut board, &avatar, x, y);
+        y += CARD_WIDTH;
+
+        if let Some(room) = card.room {
+            match resources::number_reactions(room) {
+                Ok(reaction) => imageops::overlay(&mut board, &emoji(reaction), x, y),
+                Err(err) => warn!("Could not load the number of room {}: {}", room, err),
+            }
+        }
+        y += EMOJI_HEIGHT + MARGIN;
+
+        if let Some(role) = card.role {
+            match resources::role_icon(role) {
+                Ok(icon) => {
+                    let icon =
+                        imageops::resize(&icon, ROLE_ICON_SIZE, ROLE_ICON_SIZE, imageops::Triangle);
+                    imageops::overlay(&mut board, &icon, x + (CARD_WIDTH - ROLE_ICON_SIZE) / 2, y);
+                }
+                Err(err) => warn!("Could not load the icon of {}: {}", role, err),
+            }
+        }
+    }
+
+    compositor::encode_to_png(board)
+}
+
+/// Cuts the emoji out of a reaction image and scales it to the width of a card
+fn emoji(reaction: Image) -> Image {
+    let emoji = compositor::reaction_emoji(&reaction);
+    imageops::resize(&emoji, CARD_WIDTH, EMOJI_HEIGHT, imageops::Triangle)
+}
@@ -6,13 +6,45 @@ use serenity::{
 };
 use tracing::info;
 
+/// The commands that change the running game, which gets saved after each of them
+static GAME_CHANGING_COMMANDS: [&str; 17] = [
+    "newgame",
+    "autogame",
+    "schedule",
+    "join",
+    "leave",
+    "forceadd",
+    "theme",
+    "start",
+    "nextblock",
+    "startgathering",
+    "endgathering",
+    "give",
+    "writenote",
+    "ripnote",
+    "stab",
+    "substitute",
+    "replace",
+];
+
+/// Gets the prefix used where `msg` was sent, see [`crate::data::prefix`]
+#[hook]
+pub async fn dynamic_prefix(ctx: &Context, msg: &Message) -> Option<String> {
+    Some(crate::data::prefix(ctx, msg.guild_id).await)
+}
+
 #[hook]
 pub async fn before(ctx: &Context, msg: &Message, command_name: &str) -> bool {
     info!(
+        command = command_name,
+        user = %msg.author.id,
         "Got command '{}' by user '{}'",
-        command_name, msg.author.name
+        command_name,
+        msg.author.name
     );
 
+    crate::metrics::command_started(msg.id);
+
     use crate::data::stats::CommandStatisticsContainer;
 
     ctx.data
@@ -29,14 +61,28 @@ pub async fn before(ctx: &Context, msg: &Message, command_name: &str) -> bool {
 
 #[hook]
 pub async fn after(
-    _ctx: &Context,
-    _msg: &Message,
+    ctx: &Context,
+    msg: &Message,
     command_name: &str,
     command_result: CommandResult,
 ) {
+    crate::metrics::command_finished(msg.id, command_name, command_result.is_ok());
+
     match command_result {
-        Ok(()) => info!("Processed command '{}'", command_name),
-        Err(why) => info!("Command '{}' returned error {:?}", command_name, why),
+        Ok(()) => info!(
+            command = command_name,
+            "Processed command '{}'", command_name
+        ),
+        Err(why) => info!(
+            command = command_name,
+            "Command '{}' returned error {:?}", command_name, why
+        ),
+    }
+
+    // Commands are the main way games change, so saving after each of those keeps what's on disk
+    // close to what's in memory
+    if GAME_CHANGING_COMMANDS.contains(&command_name) {
+        crate::commands::game::persist_game(ctx).await;
     }
 }
 
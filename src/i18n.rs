@@ -0,0 +1,176 @@
+//! Translations of the text players get to see
+//!
+//! The text lives in per-locale catalogues under `res/locales`, which map keys like `inspect.tv` to
+//! the text in that language. Text can contain `{placeholders}`, which get filled in by [`tr_with`].
+//! Keys missing from a locale fall back to English.
+
+use crate::storage::{GuildConfigContainer, UserLocaleContainer};
+
+use once_cell::sync::Lazy;
+use rust_embed::RustEmbed;
+use serenity::{
+    model::id::{GuildId, UserId},
+    prelude::*,
+};
+use std::{collections::HashMap, fmt::Display};
+use tracing::{error, warn};
+
+pub const FALLBACK_LOCALE: &str = "en";
+
+/// The locales shipped with the bot
+pub static LOCALES: [&str; 3] = ["en", "ro", "ja"];
+
+#[derive(RustEmbed)]
+#[folder = "res/locales"]
+struct Catalogues;
+
+type Catalogue = HashMap<String, String>;
+
+static CATALOGUES: Lazy<HashMap<&str, Catalogue>> = Lazy::new(|| {
+    LOCALES
+        .iter()
+        .map(|locale| (*locale, load(locale)))
+        .collect()
+});
+
+fn load(locale: &str) -> Catalogue {
+    let file = match Catalogues::get(&format!("{}.json", locale)) {
+        Some(file) => file,
+        None => {
+            error!("The catalogue for {} wasn't embedded", locale);
+            return Catalogue::new();
+        }
+    };
+
+    serde_json::from_slice(&file).unwrap_or_else(|e| {
+        error!("The catalogue for {} is malformed: {}", locale, e);
+        Catalogue::new()
+    })
+}
+
+pub fn is_supported(locale: &str) -> bool {
+    LOCALES.contains(&locale)
+}
+
+/// Gets the text for `key` in `locale`, without falling back to English
+pub fn lookup(locale: &str, key: &str) -> Option<&'static str> {
+    CATALOGUES
+        .get(locale)
+        .and_then(|catalogue| catalogue.get(key))
+        .map(String::as_str)
+}
+
+/// Gets the text for `key` in `locale`
+pub fn tr(locale: &str, key: &str) -> String {
+    lookup(locale, key)
+        .or_else(|| lookup(FALLBACK_LOCALE, key))
+        .map(str::to_string)
+        .unwrap_or_else(|| {
+            warn!("No text for {} in any locale", key);
+            key.to_string()
+        })
+}
+
+/// Gets the text for `key` in `locale`, replacing every `{name}` with its value from `args`
+pub fn tr_with(locale: &str, key: &str, args: &[(&str, &dyn Display)]) -> String {
+    fill(tr(locale, key), args)
+}
+
+/// Replaces every `{name}` in `text` with its value from `args`
+pub fn fill(mut text: String, args: &[(&str, &dyn Display)]) -> String {
+    for (name, value) in args {
+        text = text.replace(&format!("{{{}}}", name), &value.to_string());
+    }
+    text
+}
+
+/// Gets the names of the placeholders in `text`
+#[cfg(test)]
+pub fn placeholders(text: &str) -> std::collections::BTreeSet<&str> {
+    text.split('{')
+        .skip(1)
+        .filter_map(|rest| rest.split('}').next())
+        .collect()
+}
+
+/// Gets the locale to use with `user`: the one they picked, or else the one of the guild they're
+/// in, or else English
+pub async fn locale(ctx: &Context, guild: Option<GuildId>, user: Option<UserId>) -> String {
+    locale_from(&*ctx.data.read().await, guild, user).await
+}
+
+/// Like [`locale`] but for when `ctx.data` is already locked
+pub async fn locale_from(data: &TypeMap, guild: Option<GuildId>, user: Option<UserId>) -> String {
+    if let (Some(user), Some(locales)) = (user, data.get::<UserLocaleContainer>()) {
+        if let Some(locale) = locales.read().await.get(user) {
+            return locale;
+        }
+    }
+
+    if let (Some(guild), Some(configs)) = (guild, data.get::<GuildConfigContainer>()) {
+        return configs.read().await.get(guild).locale;
+    }
+
+    FALLBACK_LOCALE.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_locale_has_every_key() {
+        let english = &CATALOGUES[FALLBACK_LOCALE];
+        assert!(!english.is_empty(), "The English catalogue failed to load");
+
+        for locale in LOCALES.iter() {
+            let mut missing = english
+                .keys()
+                .filter(|key| lookup(locale, key).is_none())
+                .collect::<Vec<_>>();
+            missing.sort();
+
+            assert!(missing.is_empty(), "{} is missing {:?}", locale, missing);
+        }
+    }
+
+    #[test]
+    fn no_locale_has_unknown_keys() {
+        for locale in LOCALES.iter() {
+            let unknown = CATALOGUES[locale]
+                .keys()
+                .filter(|key| lookup(FALLBACK_LOCALE, key).is_none())
+                .collect::<Vec<_>>();
+
+            assert!(
+                unknown.is_empty(),
+                "{} has unknown keys {:?}",
+                locale,
+                unknown
+            );
+        }
+    }
+
+    #[test]
+    fn translations_keep_placeholders() {
+        for locale in LOCALES.iter() {
+            for (key, text) in CATALOGUES[locale].iter() {
+                assert_eq!(
+                    placeholders(text),
+                    placeholders(&tr(FALLBACK_LOCALE, key)),
+                    "{} changes the placeholders of {}",
+                    locale,
+                    key
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn placeholders_get_filled_in() {
+        assert_eq!(
+            tr_with("en", "inspect.food", &[("count", &3)]),
+            "You see 3 food bars in your bag."
+        );
+    }
+}
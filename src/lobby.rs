@@ -0,0 +1,479 @@
+//! The lobby of a game that didn't start yet
+//!
+//! Every lobby has a message that's edited in place to show who signed up, who's waiting for a spot
+//! and the rules the game is played with. Players sign up by reacting to it, or with `!join`, and
+//! once the game starts the message turns into a summary of it.
+//!
+//! Lobbies made with `!schedule` also count down to the time the game starts at. Shortly before
+//! that the players get pinged, and once it's time the game starts on its own if it's full, or is
+//! handed over to its host otherwise.
+
+use crate::{
+    commands::game::{persist_game, running_game, start_game},
+    data,
+    game::{Game, GameRules, JoinError, LeaveError},
+    helpers::{perms, react::react_with},
+    i18n, shutdown,
+};
+
+use chrono::{DateTime, Duration, Utc};
+use futures::StreamExt;
+use serenity::{
+    builder::CreateEmbed,
+    collector::ReactionCollectorBuilder,
+    framework::standard::CommandResult,
+    model::{
+        id::{ChannelId, MessageId, UserId},
+        misc::Mentionable,
+    },
+    prelude::*,
+};
+use std::{collections::HashMap, time::Instant};
+use tracing::{info, warn, Instrument};
+
+/// Reacting with this to the lobby message signs players up, and taking the reaction back makes
+/// them drop out
+static JOIN: &str = "✅";
+
+/// Reacting to the lobby message is limited just like `!join` and `!leave`: twice every 45
+/// seconds, with 5 seconds between the two
+const CONTROLS_LIMIT: usize = 2;
+const CONTROLS_TIME_SPAN: std::time::Duration = std::time::Duration::from_secs(45);
+const CONTROLS_DELAY: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// How long before a scheduled game starts its players get pinged
+const REMINDER_LEAD_MINUTES: i64 = 10;
+
+pub fn reminder_lead() -> Duration {
+    Duration::minutes(REMINDER_LEAD_MINUTES)
+}
+
+/// How signing up for a game went
+pub enum SignUp {
+    Joined,
+    /// The game was full, so the player is on its waitlist at this position
+    Waitlisted(usize),
+    /// The catalogue key of the reason the player can't sign up
+    Refused(&'static str),
+}
+
+/// Signs `user` up for `game`, or puts them on its waitlist if it's full
+pub async fn sign_up(ctx: &Context, game: &mut Game, user: UserId) -> CommandResult<SignUp> {
+    if perms::sees_every_room(ctx, game.guild(), user).await? {
+        return Ok(SignUp::Refused("join.sees_every_room"));
+    }
+
+    let sign_up = match game.join(user) {
+        Ok(()) => SignUp::Joined,
+        // Full games take sign-ups on their waitlist, for when someone leaves
+        Err(JoinError::GameFull) => match game.join_waitlist(user) {
+            Ok(()) => SignUp::Waitlisted(game.waitlist().map_or(0, |waitlist| waitlist.len())),
+            Err(err) => SignUp::Refused(err.key()),
+        },
+        Err(err) => SignUp::Refused(err.key()),
+    };
+
+    Ok(sign_up)
+}
+
+/// Takes `user` out of `game` or its waitlist, returning who got their spot
+pub fn drop_out(game: &mut Game, user: UserId) -> Result<Vec<UserId>, LeaveError> {
+    game.leave(user)?;
+    Ok(game.fill_from_waitlist())
+}
+
+/// Lets the users that got a spot in `game` from its waitlist know
+pub async fn announce_admitted(
+    ctx: &Context,
+    game: &Game,
+    channel: ChannelId,
+    admitted: &[UserId],
+) {
+    if admitted.is_empty() {
+        return;
+    }
+
+    let locale = i18n::locale(ctx, Some(game.guild()), None).await;
+    let text = i18n::tr_with(
+        &locale,
+        "lobby.admitted",
+        &[("players", &mention_list(admitted))],
+    );
+    if let Err(err) = channel.say(ctx, text).await {
+        warn!("Couldn't tell players they got into the game: {}", err);
+    }
+}
+
+/// Posts the message showing who's in `game`'s lobby in `channel`, starting with `content`
+pub async fn post(
+    ctx: &Context,
+    game: &mut Game,
+    channel: ChannelId,
+    content: &str,
+) -> CommandResult {
+    let prefix = data::prefix(ctx, Some(game.guild())).await;
+    let locale = i18n::locale(ctx, Some(game.guild()), None).await;
+    let msg = channel
+        .send_message(ctx, |m| {
+            m.content(content)
+                .set_embed(build_embed(game, &prefix, &locale))
+        })
+        .await?;
+    game.set_lobby_message(channel, msg.id);
+
+    if let Err(err) = react_with(ctx, &msg, &[JOIN]).await {
+        warn!("Couldn't add the controls to the lobby message: {}", err);
+    }
+
+    Ok(())
+}
+
+/// The embed showing who's in `game`'s lobby
+pub fn build_embed(game: &Game, prefix: &str, locale: &str) -> CreateEmbed {
+    let players = game
+        .joined_users()
+        .map(|users| users.as_slice())
+        .unwrap_or_default();
+
+    let mut embed = CreateEmbed::default();
+    embed
+        .title(i18n::tr(locale, "lobby.title"))
+        .colour(0x7289DA)
+        .field(i18n::tr(locale, "lobby.host"), game.host().mention(), true);
+
+    if let Some(starts_at) = game.scheduled_start() {
+        embed.field(
+            i18n::tr(locale, "lobby.starts"),
+            format!("<t:{0}:F> (<t:{0}:R>)", starts_at.timestamp()),
+            true,
+        );
+    }
+
+    let mut roster = mention_list(players);
+    let open_slots = 6usize.saturating_sub(players.len());
+    if open_slots > 0 {
+        if !roster.is_empty() {
+            roster.push('\n');
+        }
+        roster.push_str(&i18n::tr_with(
+            locale,
+            "lobby.open_slots",
+            &[("count", &open_slots)],
+        ));
+    }
+    embed.field(
+        i18n::tr_with(locale, "lobby.players_count", &[("count", &players.len())]),
+        roster,
+        false,
+    );
+
+    if let Some(waitlist) = game.waitlist().filter(|waitlist| !waitlist.is_empty()) {
+        embed.field(
+            i18n::tr(locale, "lobby.waitlist"),
+            mention_list(waitlist),
+            false,
+        );
+    }
+    embed.field(
+        i18n::tr(locale, "lobby.rules"),
+        describe_rules(game.rules(), locale),
+        false,
+    );
+    embed.footer(|f| {
+        f.text(i18n::tr_with(
+            locale,
+            "lobby.footer",
+            &[("emoji", &JOIN), ("prefix", &prefix)],
+        ))
+    });
+
+    embed
+}
+
+/// The embed the lobby message turns into once `game` started
+fn build_summary(game: &Game, locale: &str) -> CreateEmbed {
+    let players = game
+        .players()
+        .map(|players| players.keys().copied().collect::<Vec<_>>())
+        .unwrap_or_default();
+
+    let mut embed = CreateEmbed::default();
+    embed
+        .title(i18n::tr(locale, "lobby.started_title"))
+        .colour(0x43B581)
+        .field(i18n::tr(locale, "lobby.host"), game.host().mention(), true)
+        .field(
+            i18n::tr(locale, "lobby.started"),
+            format!("<t:{}:R>", Utc::now().timestamp()),
+            true,
+        )
+        .field(
+            i18n::tr(locale, "lobby.players"),
+            mention_list(&players),
+            false,
+        )
+        .field(
+            i18n::tr(locale, "lobby.rules"),
+            describe_rules(game.rules(), locale),
+            false,
+        );
+
+    embed
+}
+
+/// Edits the lobby message of `game`, if it has one, so it shows who's in the lobby now
+pub async fn refresh(ctx: &Context, game: &Game) {
+    let prefix = data::prefix(ctx, Some(game.guild())).await;
+    let locale = i18n::locale(ctx, Some(game.guild()), None).await;
+    edit(ctx, game, build_embed(game, &prefix, &locale)).await;
+}
+
+/// Turns the lobby message of `game` into a summary of the game that started
+pub async fn show_started(ctx: &Context, game: &Game) {
+    let locale = i18n::locale(ctx, Some(game.guild()), None).await;
+    edit(ctx, game, build_summary(game, &locale)).await;
+}
+
+/// Marks the lobby message of a game that was called off before it started
+pub async fn close(ctx: &Context, game: &Game) {
+    let locale = i18n::locale(ctx, Some(game.guild()), None).await;
+    let mut embed = CreateEmbed::default();
+    embed
+        .title(i18n::tr(&locale, "lobby.title"))
+        .colour(0x99AAB5)
+        .description(i18n::tr(&locale, "lobby.called_off"));
+    edit(ctx, game, embed).await;
+}
+
+async fn edit(ctx: &Context, game: &Game, embed: CreateEmbed) {
+    let (channel, message) = match game.lobby_message() {
+        Some(lobby_message) => lobby_message,
+        None => return,
+    };
+
+    if let Err(err) = channel
+        .edit_message(ctx, message, |m| {
+            m.embed(|e| {
+                *e = embed;
+                e
+            })
+        })
+        .await
+    {
+        warn!("Couldn't update the lobby message: {}", err);
+    }
+}
+
+/// Signs up and drops out players that react to the lobby message of `game`, until it starts
+pub fn watch_controls(ctx: Context, game: &Game) {
+    if let Some((channel, message)) = game.lobby_message() {
+        tokio::spawn(run_controls(ctx, game.id(), channel, message).instrument(game.span()));
+    }
+}
+
+async fn run_controls(ctx: Context, id: u64, channel: ChannelId, message: MessageId) {
+    let shutdown = shutdown::get(&ctx).await;
+    let me = ctx.cache.current_user_id().await;
+    let mut uses = HashMap::new();
+    let mut reactions = ReactionCollectorBuilder::new(&ctx)
+        .message_id(message)
+        .added(true)
+        .removed(true)
+        .filter(|reaction| reaction.emoji.unicode_eq(JOIN))
+        .await;
+
+    loop {
+        let action = tokio::select! {
+            action = reactions.next() => match action {
+                Some(action) => action,
+                None => return,
+            },
+            _ = shutdown.wait() => return,
+        };
+        let user = match action.as_inner_ref().user_id {
+            Some(user) if user != me => user,
+            _ => continue,
+        };
+        if !use_controls(&mut uses, user) {
+            info!("Ignored a user reacting to the lobby message too often");
+            continue;
+        }
+
+        // Whoever holds the game may be waiting on ctx.data, so this is looked up before locking it
+        let locale = i18n::locale(&ctx, action.as_inner_ref().guild_id, Some(user)).await;
+        let game = match running_game(&ctx, id).await {
+            Some(game) => game,
+            None => return,
+        };
+        {
+            let mut game = game.write().await;
+            if game.is_started() {
+                return;
+            }
+
+            if action.is_added() {
+                match sign_up(&ctx, &mut game, user).await {
+                    Ok(SignUp::Refused(reason)) => {
+                        let reason = i18n::tr(&locale, reason);
+                        let _ = channel
+                            .say(&ctx, format!("{}: {}", user.mention(), reason))
+                            .await
+                            .map_err(|e| warn!("Couldn't tell a user why they can't join: {}", e));
+                    }
+                    Ok(_) => info!("A user signed up through the lobby message"),
+                    Err(err) => warn!("Couldn't sign a user up: {}", err),
+                }
+            } else if let Ok(admitted) = drop_out(&mut game, user) {
+                info!("A user dropped out through the lobby message");
+                announce_admitted(&ctx, &game, channel, &admitted).await;
+            }
+            refresh(&ctx, &game).await;
+        }
+        persist_game(&ctx).await;
+    }
+}
+
+/// Records that `user` reacted to the lobby message, unless they did so too often lately
+fn use_controls(uses: &mut HashMap<UserId, Vec<Instant>>, user: UserId) -> bool {
+    let now = Instant::now();
+    let used_at = uses.entry(user).or_default();
+    used_at.retain(|used| now - *used < CONTROLS_TIME_SPAN);
+
+    let too_soon = used_at
+        .last()
+        .map_or(false, |last| now - *last < CONTROLS_DELAY);
+    if used_at.len() >= CONTROLS_LIMIT || too_soon {
+        return false;
+    }
+
+    used_at.push(now);
+    true
+}
+
+/// Pings the players of a scheduled game shortly before it starts, and starts it or hands it over
+/// to its host once it's time
+pub fn follow_schedule(ctx: Context, game: &Game) {
+    tokio::spawn(run_schedule(ctx, game.id()).instrument(game.span()));
+}
+
+async fn run_schedule(ctx: Context, id: u64) {
+    let shutdown = shutdown::get(&ctx).await;
+    loop {
+        let wake_at = match running_game(&ctx, id).await {
+            Some(game) => {
+                let game = game.read().await;
+                match game.scheduled_start() {
+                    Some(starts_at) if !game.players_reminded() => starts_at - reminder_lead(),
+                    Some(starts_at) => starts_at,
+                    None => return,
+                }
+            }
+            None => return,
+        };
+
+        tokio::select! {
+            _ = tokio::time::sleep((wake_at - Utc::now()).to_std().unwrap_or_default()) => {}
+            // The schedule is saved with the game, and followed again once the bot is back
+            _ = shutdown.wait() => return,
+        }
+
+        let game = match running_game(&ctx, id).await {
+            Some(game) => game,
+            None => return,
+        };
+        {
+            let mut game = game.write().await;
+            let starts_at = match game.scheduled_start() {
+                Some(starts_at) if !game.is_started() => starts_at,
+                _ => return,
+            };
+
+            if !game.players_reminded() && Utc::now() < starts_at {
+                remind_players(&ctx, &game, starts_at).await;
+                game.set_players_reminded();
+            } else {
+                game.unschedule();
+                begin(&ctx, &mut game).await;
+            }
+        }
+        persist_game(&ctx).await;
+    }
+}
+
+async fn remind_players(ctx: &Context, game: &Game, starts_at: DateTime<Utc>) {
+    let players = game
+        .joined_users()
+        .map(|users| users.as_slice())
+        .unwrap_or_default();
+    if players.is_empty() {
+        return;
+    }
+
+    let locale = i18n::locale(ctx, Some(game.guild()), None).await;
+    let text = i18n::tr_with(
+        &locale,
+        "lobby.reminder",
+        &[
+            ("players", &mention_list(players)),
+            ("time", &format!("<t:{}:R>", starts_at.timestamp())),
+        ],
+    );
+    if let Err(err) = game.announcement_channel().say(ctx, text).await {
+        warn!("Couldn't remind the players of the game: {}", err);
+    }
+}
+
+/// Starts the game now that it's time, or lets the host know they'll have to do it
+async fn begin(ctx: &Context, game: &mut Game) {
+    let channel = game.announcement_channel();
+    let host = game.host().mention();
+    let prefix = data::prefix(ctx, Some(game.guild())).await;
+    let locale = i18n::locale(ctx, Some(game.guild()), None).await;
+
+    let text = if game.can_start() {
+        info!("Starting the scheduled game");
+        let key = match start_game(ctx, game, channel).await {
+            Ok(true) => "lobby.begin.started",
+            Ok(false) => "lobby.begin.problems",
+            Err(err) => {
+                warn!("Couldn't start the scheduled game: {}", err);
+                "lobby.begin.failed"
+            }
+        };
+        i18n::tr_with(&locale, key, &[("host", &host), ("prefix", &prefix)])
+    } else {
+        let players = game.joined_users().map_or(0, |users| users.len());
+        i18n::tr_with(
+            &locale,
+            "lobby.begin.not_full",
+            &[("host", &host), ("count", &players), ("prefix", &prefix)],
+        )
+    };
+
+    if let Err(err) = channel.say(ctx, text).await {
+        warn!("Couldn't hand the game over to its host: {}", err);
+    }
+    if !game.is_started() {
+        refresh(ctx, game).await;
+    }
+}
+
+fn mention_list(users: &[UserId]) -> String {
+    users
+        .iter()
+        .map(|user| user.mention().to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn describe_rules(rules: &GameRules, locale: &str) -> String {
+    i18n::tr_with(
+        locale,
+        "lobby.rules_summary",
+        &[
+            ("food", &rules.food_portions),
+            ("notes", &rules.max_notes),
+            ("minutes", &rules.decision_timeout_minutes),
+        ],
+    )
+}
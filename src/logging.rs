@@ -0,0 +1,69 @@
+//! Sets up where logs go and what they look like, following the `[log]` section of the config
+//!
+//! Everything that happens in a game is logged inside its span, see `Game::span`, so with the JSON
+//! format a single game's history can be pulled out of the logs with e.g.
+//! `jq 'select(.spans[]?.game_id == 1234)'`.
+
+use crate::config::{LogConfig, LogFormat, Rotation};
+
+use tracing_appender::{non_blocking::WorkerGuard, rolling};
+use tracing_subscriber::{fmt::MakeWriter, EnvFilter};
+
+pub type Error = Box<dyn std::error::Error + Send + Sync>;
+
+/// What log files are called, before the date of the period they cover
+const FILE_PREFIX: &str = "kingdom-royale-maid.log";
+
+/// Keeps the thread that writes log files running, events logged after it's dropped are lost
+#[must_use = "logs stop being written to files when the guard is dropped"]
+pub struct Guard {
+    _worker: Option<WorkerGuard>,
+}
+
+/// Starts logging, to stderr instead of stdout if `to_stderr` is set and no directory is configured
+pub fn init(config: &LogConfig, to_stderr: bool) -> Result<Guard, Error> {
+    let filter = EnvFilter::try_new(&config.level)
+        .map_err(|err| format!("Invalid log level `{}`: {}", config.level, err))?;
+
+    let directory = match &config.directory {
+        Some(directory) => directory,
+        None if to_stderr => {
+            install(config, filter, std::io::stderr, config.ansi);
+            return Ok(Guard { _worker: None });
+        }
+        None => {
+            install(config, filter, std::io::stdout, config.ansi);
+            return Ok(Guard { _worker: None });
+        }
+    };
+
+    let appender = match config.rotation {
+        Rotation::Hourly => rolling::hourly(directory, FILE_PREFIX),
+        Rotation::Daily => rolling::daily(directory, FILE_PREFIX),
+        Rotation::Never => rolling::never(directory, FILE_PREFIX),
+    };
+    let (writer, guard) = tracing_appender::non_blocking(appender);
+    install(config, filter, writer, false);
+
+    Ok(Guard {
+        _worker: Some(guard),
+    })
+}
+
+fn install<W>(config: &LogConfig, filter: EnvFilter, writer: W, ansi: bool)
+where
+    W: MakeWriter + Send + Sync + 'static,
+{
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(writer);
+
+    match config.format {
+        LogFormat::Human => builder.with_ansi(ansi).init(),
+        LogFormat::Json => builder
+            .json()
+            .with_current_span(true)
+            .with_span_list(true)
+            .init(),
+    }
+}
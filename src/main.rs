@@ -1,58 +1,128 @@
 use bot::Bot;
-use serenity::{
-    client::bridge::gateway::ShardManager, framework::standard::CommandResult, model::id::ChannelId,
-};
-use std::sync::Arc;
-use tokio::sync::Mutex;
+use cli::{Command, Opt};
+use config::Config;
+use serenity::{framework::standard::CommandResult, http::Http, model::id::GuildId, prelude::*};
+use shutdown::ShutdownContainer;
+use std::{sync::Arc, time::Instant};
+use storage::Storage;
+use structopt::StructOpt;
 use tracing::{error, info, instrument};
 
+mod absence;
+mod autopilot;
 mod bot;
+mod cli;
 mod commands;
+mod config;
 mod data;
 mod game;
 mod helpers;
 mod hooks;
+mod i18n;
+mod lobby;
+mod logging;
+mod metrics;
+mod recovery;
 mod resources;
+mod shutdown;
+mod storage;
+mod systemd;
+mod themes;
 #[cfg(not(feature = "deterministic"))]
 mod version_data;
 
 #[tokio::main]
 #[instrument]
 async fn main() -> CommandResult {
-    tracing_subscriber::fmt::init();
+    let startup_time = Instant::now();
 
-    let startup_time = std::time::Instant::now();
+    let opt = Opt::from_args();
+    let command = opt.command.unwrap_or(Command::Run);
 
-    let (token, prefix, cdn_channel_id) = get_env_config();
+    let config = Config::load(opt.config.as_deref(), command.needs_token());
+    if let Command::CheckConfig = command {
+        return cli::check_config(config);
+    }
 
-    let mut bot = Bot::new(
-        token,
-        prefix,
-        ChannelId(cdn_channel_id.parse::<u64>()?),
-        startup_time,
-    )
-    .await;
+    let mut config = match config {
+        Ok(config) => config,
+        Err(problems) => {
+            tracing_subscriber::fmt()
+                .with_writer(std::io::stderr)
+                .init();
+            for problem in &problems {
+                error!("Invalid configuration: {}", problem);
+            }
+            return Err("The configuration has problems, see the check-config command".into());
+        }
+    };
+    if let Some(level) = opt.log_level {
+        config.log.level = level;
+    }
+    let logging = logging::init(&config.log, command.prints_output())?;
+
+    match command {
+        Command::Run => {
+            if run(config, startup_time).await? {
+                // Whatever is still buffered has to be written before the process gets replaced
+                drop(logging);
+                return restart();
+            }
+            Ok(())
+        }
+        Command::CheckConfig => unreachable!("check-config returns before logging is set up"),
+        Command::Migrate => cli::migrate(&config),
+        Command::ExportGame { id, output } => cli::export_game(&config, id, output),
+        Command::Simulate { games, seed } => cli::simulate(&config, games, seed),
+        Command::PurgeGuildData { guild, yes } => {
+            cli::purge_guild_data(&config, GuildId(guild), yes)
+        }
+    }
+}
+
+/// Runs the bot until it shuts down, returning whether it should start again
+async fn run(config: Config, startup_time: Instant) -> CommandResult<bool> {
+    let storage = Storage::open(&config.storage_path)?;
+    let metrics_address = config.metrics.address;
+    let mut bot = Bot::new(config, storage, startup_time).await;
 
-    setup_signals(bot.shard_manager()).await;
+    if let Some(address) = metrics_address {
+        tokio::spawn(metrics::serve(address, bot.data()));
+    }
+
+    setup_signals(bot.data(), bot.http()).await;
+    systemd::spawn_watchdog(bot.shard_manager());
 
     if let Err(why) = bot.start().await {
         error!("Client error: {:?}", why);
     }
 
-    Ok(())
+    let data = bot.data();
+    let data = data.read().await;
+    let shutdown = data
+        .get::<ShutdownContainer>()
+        .expect("the bot's data should always have a ShutdownContainer");
+    Ok(shutdown.restart_requested())
 }
 
-fn get_env_config() -> (String, String, String) {
-    dotenv::dotenv().expect("Encountered an error that didn't allow parsing the .env file");
+/// Replaces the process with a fresh copy of the bot, started with the same arguments
+#[cfg(unix)]
+fn restart() -> CommandResult {
+    use std::os::unix::process::CommandExt;
 
-    let token = dotenv::var("DISCORD_TOKEN").expect("Expected a token in the environment");
-    let prefix = dotenv::var("MAID_PREFIX").unwrap_or_else(|_| "!".into());
-    let cdn_channel_id = dotenv::var("MAID_CDN_CHANNEL_ID").expect("Give me my discord cdn pl0x");
+    info!("Starting again...");
+    let err = std::process::Command::new(std::env::current_exe()?)
+        .args(std::env::args_os().skip(1))
+        .exec();
+    Err(format!("Couldn't start the bot again: {}", err).into())
+}
 
-    (token, prefix, cdn_channel_id)
+#[cfg(not(unix))]
+fn restart() -> CommandResult {
+    Err("Restarting isn't supported on this platform, start the bot again manually".into())
 }
 
-async fn setup_signals(shard_manager: Arc<Mutex<ShardManager>>) {
+async fn setup_signals(data: Arc<RwLock<TypeMap>>, http: Arc<Http>) {
     // Listen to interrupts
     // Thanks Prof Bloodstone from the serenity discord
     #[cfg(not(windows))]
@@ -66,11 +136,12 @@ async fn setup_signals(shard_manager: Arc<Mutex<ShardManager>>) {
         ];
         for kind in signals_to_handle {
             let mut stream = signal(kind).unwrap();
-            let shard_manager = shard_manager.clone();
+            let data = Arc::clone(&data);
+            let http = Arc::clone(&http);
             tokio::spawn(async move {
                 stream.recv().await;
                 info!("Signal received - shutting down!");
-                shard_manager.lock().await.shutdown_all().await;
+                shutdown::shut_down(&data, &http, false).await;
             });
         }
     }
@@ -79,18 +150,19 @@ async fn setup_signals(shard_manager: Arc<Mutex<ShardManager>>) {
     {
         use tokio::signal::windows::{ctrl_break, ctrl_c};
 
-        let shard_manager_clone = Arc::clone(shard_manager);
+        let data_clone = Arc::clone(&data);
+        let http_clone = Arc::clone(&http);
 
         tokio::spawn(async move {
             ctrl_break().unwrap().recv().await;
             info!("Ctrl Break received - shutting down!");
-            shard_manager_clone.lock().await.shutdown_all().await;
+            shutdown::shut_down(&data_clone, &http_clone, false).await;
         });
 
         tokio::spawn(async move {
             ctrl_c().unwrap().recv().await;
             info!("Ctrl C received - shutting down!");
-            shard_manager.lock().await.shutdown_all().await;
+            shutdown::shut_down(&data, &http, false).await;
         });
     }
 }
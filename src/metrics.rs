@@ -0,0 +1,237 @@
+//! Metrics about the bot, served in the Prometheus text format at `/metrics` when
+//! `metrics.address` is set in the config
+//!
+//! Everything is counted whether the endpoint is on or not, recording a metric is cheap enough.
+
+use crate::commands::game::GameContainer;
+
+use hyper::{
+    header::CONTENT_TYPE,
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use once_cell::sync::Lazy;
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec, Opts,
+    Registry, TextEncoder,
+};
+use serenity::{model::id::MessageId, prelude::*};
+use std::{
+    collections::HashMap,
+    convert::Infallible,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+use tracing::{error, info, warn};
+
+static METRICS: Lazy<Metrics> = Lazy::new(Metrics::new);
+
+struct Metrics {
+    registry: Registry,
+    commands: IntCounterVec,
+    command_duration: HistogramVec,
+    /// When the commands that are still running started, by the message that invoked them
+    running_commands: Mutex<HashMap<MessageId, Instant>>,
+    active_games: IntGauge,
+    block_transitions: IntCounterVec,
+    render_duration: HistogramVec,
+    avatar_fetch_failures: IntCounter,
+    memory: IntGaugeVec,
+}
+
+impl Metrics {
+    fn new() -> Self {
+        let commands = IntCounterVec::new(
+            Opts::new("maid_commands_total", "Commands that were run"),
+            &["command", "outcome"],
+        )
+        .unwrap();
+        let command_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "maid_command_duration_seconds",
+                "How long commands took to run",
+            )
+            .buckets(vec![0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0]),
+            &["command"],
+        )
+        .unwrap();
+        let active_games = IntGauge::new("maid_active_games", "Games that aren't over").unwrap();
+        let block_transitions = IntCounterVec::new(
+            Opts::new(
+                "maid_block_transitions_total",
+                "Times games moved on to a time block",
+            ),
+            &["block"],
+        )
+        .unwrap();
+        let render_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "maid_image_render_duration_seconds",
+                "How long rendering images took",
+            )
+            .buckets(vec![0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5]),
+            &["image"],
+        )
+        .unwrap();
+        let avatar_fetch_failures = IntCounter::new(
+            "maid_avatar_fetch_failures_total",
+            "Avatars that couldn't be fetched and got replaced by generated ones",
+        )
+        .unwrap();
+        let memory = IntGaugeVec::new(
+            Opts::new("maid_memory_bytes", "Memory used by the bot"),
+            &["kind"],
+        )
+        .unwrap();
+
+        let registry = Registry::new();
+        registry.register(Box::new(commands.clone())).unwrap();
+        registry
+            .register(Box::new(command_duration.clone()))
+            .unwrap();
+        registry.register(Box::new(active_games.clone())).unwrap();
+        registry
+            .register(Box::new(block_transitions.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(render_duration.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(avatar_fetch_failures.clone()))
+            .unwrap();
+        registry.register(Box::new(memory.clone())).unwrap();
+
+        Self {
+            registry,
+            commands,
+            command_duration,
+            running_commands: Mutex::new(HashMap::new()),
+            active_games,
+            block_transitions,
+            render_duration,
+            avatar_fetch_failures,
+            memory,
+        }
+    }
+}
+
+/// Starts timing the command invoked by `msg`
+pub fn command_started(msg: MessageId) {
+    METRICS
+        .running_commands
+        .lock()
+        .unwrap()
+        .insert(msg, Instant::now());
+}
+
+/// Counts a command that finished running, and how long it took if it was started
+pub fn command_finished(msg: MessageId, command: &str, succeeded: bool) {
+    let outcome = if succeeded { "ok" } else { "error" };
+    METRICS
+        .commands
+        .with_label_values(&[command, outcome])
+        .inc();
+
+    let started = METRICS.running_commands.lock().unwrap().remove(&msg);
+    if let Some(started) = started {
+        METRICS
+            .command_duration
+            .with_label_values(&[command])
+            .observe(started.elapsed().as_secs_f64());
+    }
+}
+
+/// Counts a game moving on to `block`
+pub fn block_transition(block: &str) {
+    METRICS.block_transitions.with_label_values(&[block]).inc();
+}
+
+/// Renders an image with `render`, timing it under the name `image`
+pub fn time_render<T>(image: &str, render: impl FnOnce() -> T) -> T {
+    let _timer = METRICS
+        .render_duration
+        .with_label_values(&[image])
+        .start_timer();
+    render()
+}
+
+pub fn avatar_fetch_failed() {
+    METRICS.avatar_fetch_failures.inc();
+}
+
+/// Serves the metrics at `address` until the bot stops, reading the state of games from `data`
+pub async fn serve(address: SocketAddr, data: Arc<RwLock<TypeMap>>) {
+    let make_service = make_service_fn(move |_| {
+        let data = Arc::clone(&data);
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                respond(request, Arc::clone(&data))
+            }))
+        }
+    });
+
+    let server = match Server::try_bind(&address) {
+        Ok(builder) => builder.serve(make_service),
+        Err(err) => {
+            error!("Couldn't serve metrics at {}: {}", address, err);
+            return;
+        }
+    };
+
+    info!("Serving metrics at http://{}/metrics", address);
+    if let Err(err) = server.await {
+        error!("The metrics server stopped: {}", err);
+    }
+}
+
+async fn respond(
+    request: Request<Body>,
+    data: Arc<RwLock<TypeMap>>,
+) -> Result<Response<Body>, Infallible> {
+    if request.method() != Method::GET || request.uri().path() != "/metrics" {
+        return Ok(status(StatusCode::NOT_FOUND));
+    }
+
+    update_gauges(&data).await;
+
+    let encoder = TextEncoder::new();
+    let mut buffer = vec![];
+    if let Err(err) = encoder.encode(&METRICS.registry.gather(), &mut buffer) {
+        warn!("Couldn't encode the metrics: {}", err);
+        return Ok(status(StatusCode::INTERNAL_SERVER_ERROR));
+    }
+
+    Ok(Response::builder()
+        .header(CONTENT_TYPE, encoder.format_type())
+        .body(Body::from(buffer))
+        .expect("the response is always valid"))
+}
+
+fn status(status: StatusCode) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .body(Body::empty())
+        .expect("the response is always valid")
+}
+
+/// Updates the metrics that are only read when they get scraped
+async fn update_gauges(data: &RwLock<TypeMap>) {
+    let game = data.read().await.get::<GameContainer>().cloned();
+    let active_games = match game {
+        Some(game) if !game.read().await.is_ended() => 1,
+        _ => 0,
+    };
+    METRICS.active_games.set(active_games);
+
+    #[cfg(target_os = "linux")]
+    match crate::data::stats::memory_usage() {
+        Ok(usage) => {
+            let memory = &METRICS.memory;
+            memory.with_label_values(&["uss"]).set(usage.uss as i64);
+            memory.with_label_values(&["pss"]).set(usage.pss as i64);
+            memory.with_label_values(&["rss"]).set(usage.rss as i64);
+        }
+        Err(err) => warn!("Couldn't read the memory usage: {}", err),
+    }
+}
@@ -0,0 +1,172 @@
+//! Deals with games that were running when the bot stopped
+//!
+//! Every game's Discord objects are written down on disk, so when the bot comes back it asks the
+//! host of such a game whether to resume it or to clean up after it, resuming it if they don't
+//! answer in time. Games the bot hosts itself are just resumed.
+
+use crate::{
+    absence, autopilot,
+    commands::game::{persist_game, GameContainer},
+    game::Game,
+    helpers::react::react_with,
+    i18n, lobby,
+    storage::{self, StorageContainer},
+};
+
+use serenity::{model::misc::Mentionable, prelude::*};
+use std::{sync::Arc, time::Duration};
+use tracing::{error, info, warn, Instrument};
+
+static RESUME: &str = "▶️";
+static CLEAN_UP: &str = "🧹";
+
+/// How long the host has to answer before the game is resumed for them
+const ANSWER_TIMEOUT_MINUTES: u64 = 30;
+
+/// Asks the hosts of all unfinished games what to do with them
+pub async fn offer_recovery(ctx: Context) {
+    let storage = ctx
+        .data
+        .read()
+        .await
+        .get::<StorageContainer>()
+        .cloned()
+        .expect("ctx.data should always have a StorageContainer");
+
+    let records = match storage::unfinished_games(&storage) {
+        Ok(records) => records,
+        Err(err) => {
+            error!("Couldn't look for unfinished games: {}", err);
+            return;
+        }
+    };
+
+    for record in records {
+        let span = record.game.span();
+        info!(parent: &span, "Found an unfinished game");
+        tokio::spawn(ask_host(ctx.clone(), record.game).instrument(span));
+    }
+}
+
+async fn ask_host(ctx: Context, game: Game) {
+    let channel = game.announcement_channel();
+    let locale = i18n::locale(&ctx, Some(game.guild()), Some(game.host())).await;
+    if game.is_automated() {
+        let reply = resume(&ctx, game).await;
+        if let Err(err) = channel.say(&ctx, i18n::tr(&locale, reply)).await {
+            warn!("{}", err);
+        }
+        return;
+    }
+
+    let msg = channel
+        .say(
+            &ctx,
+            i18n::tr_with(
+                &locale,
+                "recovery.ask",
+                &[
+                    ("host", &game.host().mention()),
+                    ("state", &game.state_name()),
+                    ("resume", &RESUME),
+                    ("clean_up", &CLEAN_UP),
+                    ("minutes", &ANSWER_TIMEOUT_MINUTES),
+                ],
+            ),
+        )
+        .await;
+    let msg = match msg {
+        Ok(msg) => msg,
+        Err(err) => {
+            warn!("Couldn't ask about game {}: {}", game.id(), err);
+            return;
+        }
+    };
+
+    if let Err(err) = react_with(&ctx, &msg, &[RESUME, CLEAN_UP]).await {
+        warn!("{}", err);
+    }
+
+    let reaction = msg
+        .await_reaction(&ctx)
+        .author_id(game.host())
+        .filter(|r| [RESUME, CLEAN_UP].contains(&r.emoji.to_string().as_str()))
+        .timeout(Duration::from_secs(ANSWER_TIMEOUT_MINUTES * 60))
+        .await;
+
+    let reply = match reaction {
+        Some(reaction) if reaction.as_inner_ref().emoji.unicode_eq(CLEAN_UP) => {
+            clean_up(&ctx, game).await
+        }
+        Some(_) => resume(&ctx, game).await,
+        None => {
+            info!(
+                "The host didn't answer in time, resuming game {}",
+                game.id()
+            );
+            resume(&ctx, game).await
+        }
+    };
+
+    if let Err(err) = channel.say(&ctx, i18n::tr(&locale, reply)).await {
+        warn!("{}", err);
+    }
+}
+
+/// Makes `game` the running game again, returning the catalogue key of the reply to the host
+async fn resume(ctx: &Context, mut game: Game) -> &'static str {
+    if ctx.data.read().await.get::<GameContainer>().is_some() {
+        return "recovery.already_running";
+    }
+
+    info!("Resuming game {}", game.id());
+    // Nobody else can see the game yet, so its prompts can be looked up without locking anything
+    let prompts = game.resume_decisions(ctx).await;
+
+    {
+        let mut data = ctx.data.write().await;
+        if data.get::<GameContainer>().is_some() {
+            return "recovery.already_running";
+        }
+
+        // These look the game up once they run, which they can't before ctx.data is released
+        game.wait_for_resumed_decisions(ctx, prompts);
+        if game.is_automated() {
+            autopilot::spawn(ctx.clone(), &game);
+        }
+        if game.scheduled_start().is_some() {
+            lobby::follow_schedule(ctx.clone(), &game);
+        }
+        if !game.is_started() {
+            lobby::watch_controls(ctx.clone(), &game);
+        }
+        absence::ask_again(ctx, &game);
+        data.insert::<GameContainer>(Arc::new(RwLock::new(game)));
+    }
+    // The prompts that were posted again have to be saved
+    persist_game(ctx).await;
+
+    "recovery.resumed"
+}
+
+/// Undoes everything the bot did for `game`, and marks it as finished, returning the catalogue key
+/// of the reply to the host
+pub async fn clean_up(ctx: &Context, game: Game) -> &'static str {
+    info!("Cleaning up after game {}", game.id());
+    game.created_objects()
+        .roll_back(ctx, game.guild(), game.player_role())
+        .await;
+
+    let storage = ctx
+        .data
+        .read()
+        .await
+        .get::<StorageContainer>()
+        .cloned()
+        .expect("ctx.data should always have a StorageContainer");
+    if let Err(err) = storage::save_game(&storage, &game, true) {
+        warn!("Couldn't mark game {} as finished: {}", game.id(), err);
+    }
+
+    "recovery.cleaned_up"
+}
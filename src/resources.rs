@@ -30,3 +30,10 @@ pub fn get_skull() -> CommandResult<RgbaImage> {
     let dynimg = load_from_memory_with_format(&*res, ImageFormat::Png)?;
     Ok(dynimg.to_rgba8())
 }
+
+/// The icon of a role, `role` being its key (see [`crate::game::RoleName::key`])
+pub fn role_icon(role: &str) -> CommandResult<RgbaImage> {
+    let res = Assets::get(&format!("{}.png", role)).ok_or("Unknown role")?;
+    let dynimg = load_from_memory_with_format(&*res, ImageFormat::Png)?;
+    Ok(dynimg.to_rgba8())
+}
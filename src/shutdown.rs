@@ -0,0 +1,159 @@
+//! Stopping the bot without ending the game that's running
+//!
+//! The game gets frozen instead: players waiting on a decision stop being waited on, the players
+//! are told the maid will be back, and the game is saved so the host can resume it once the bot
+//! starts again, see `crate::recovery`.
+
+use crate::{
+    bot::ShardManagerContainer,
+    commands::game::GameContainer,
+    i18n,
+    storage::{self, StorageContainer},
+    systemd,
+};
+
+use serenity::{http::Http, prelude::*};
+use std::{
+    future,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+pub struct ShutdownContainer;
+
+impl TypeMapKey for ShutdownContainer {
+    type Value = Arc<Shutdown>;
+}
+
+pub struct Shutdown {
+    started: AtomicBool,
+    restart: AtomicBool,
+    sender: watch::Sender<bool>,
+    receiver: watch::Receiver<bool>,
+}
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        let (sender, receiver) = watch::channel(false);
+        Self {
+            started: AtomicBool::new(false),
+            restart: AtomicBool::new(false),
+            sender,
+            receiver,
+        }
+    }
+}
+
+impl Shutdown {
+    /// Marks the bot as shutting down, returning `false` if it already was
+    fn begin(&self, restart: bool) -> bool {
+        if self.started.swap(true, Ordering::SeqCst) {
+            return false;
+        }
+
+        self.restart.store(restart, Ordering::SeqCst);
+        let _ = self.sender.send(true);
+        true
+    }
+
+    /// Waits until the bot starts shutting down
+    pub async fn wait(&self) {
+        let mut receiver = self.receiver.clone();
+        while !*receiver.borrow() {
+            if receiver.changed().await.is_err() {
+                // The sender lives as long as `self`, so this can't really happen
+                future::pending::<()>().await;
+            }
+        }
+    }
+
+    /// Whether the bot should start again once it has shut down
+    pub fn restart_requested(&self) -> bool {
+        self.restart.load(Ordering::SeqCst)
+    }
+}
+
+/// Gets the `Shutdown` out of `ctx.data`
+pub async fn get(ctx: &Context) -> Arc<Shutdown> {
+    ctx.data
+        .read()
+        .await
+        .get::<ShutdownContainer>()
+        .cloned()
+        .expect("ctx.data should always have a ShutdownContainer")
+}
+
+/// Freezes the running game and disconnects from Discord, which makes the bot exit or, if
+/// `restart` is set, start again
+///
+/// It takes the parts of a `Context` it needs, so it can be called when a signal is received too.
+pub async fn shut_down(data: &RwLock<TypeMap>, http: &Http, restart: bool) {
+    let (shutdown, game, storage, shard_manager) = {
+        let data = data.read().await;
+        (
+            data.get::<ShutdownContainer>()
+                .cloned()
+                .expect("ctx.data should always have a ShutdownContainer"),
+            data.get::<GameContainer>().cloned(),
+            data.get::<StorageContainer>()
+                .cloned()
+                .expect("ctx.data should always have a StorageContainer"),
+            data.get::<ShardManagerContainer>()
+                .cloned()
+                .expect("ctx.data should always have a ShardManagerContainer"),
+        )
+    };
+
+    if !shutdown.begin(restart) {
+        info!("Already shutting down");
+        return;
+    }
+
+    if restart {
+        info!("Restarting...");
+        systemd::reloading();
+    } else {
+        info!("Shutting down...");
+        systemd::stopping();
+    }
+
+    // Whoever holds the game may be waiting on `data`, so this is looked up before locking it
+    let locale = match &game {
+        Some(game) => {
+            let guild = game.read().await.guild();
+            i18n::locale_from(&*data.read().await, Some(guild), None).await
+        }
+        None => i18n::FALLBACK_LOCALE.to_string(),
+    };
+
+    // Holding on to the game until the shards are shut down keeps anything from changing it after
+    // it's saved
+    let game = match &game {
+        Some(game) => Some(game.write().await),
+        None => None,
+    };
+
+    if let Some(game) = &game {
+        let span = game.span();
+        if !game.is_ended() {
+            if let Err(err) = game
+                .meeting_room()
+                .say(http, i18n::tr(&locale, "shutdown.notice"))
+                .await
+            {
+                warn!(parent: &span, "Couldn't tell the players about the shutdown: {}", err);
+            }
+        }
+
+        match storage::save_game(&storage, game, false) {
+            Ok(()) => info!(parent: &span, "Saved the game"),
+            Err(err) => warn!(parent: &span, "Couldn't save the game: {}", err),
+        }
+    }
+
+    shard_manager.lock().await.shutdown_all().await;
+}
@@ -0,0 +1,148 @@
+use super::{Error, Storage};
+
+use serenity::prelude::TypeMapKey;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, SystemTime},
+};
+use tracing::{info, warn};
+
+/// Avatars downloaded from Discord, stored by their avatar hash
+///
+/// A user's avatar hash changes whenever they change their avatar, so an entry never has to be
+/// invalidated, it's only refreshed once in a while in case Discord re-encodes the image.
+/// Entries that are too old to be used are still kept around for some time, as they're better than
+/// a generated avatar when Discord can't be reached.
+pub struct AvatarCache {
+    dir: PathBuf,
+    /// How many bytes the cache can take up, the least recently downloaded avatars go first
+    max_size: u64,
+    /// How many bytes the cache takes up, as of the last pruning and the insertions since
+    size: AtomicU64,
+}
+
+impl AvatarCache {
+    const DIR_NAME: &'static str = "avatar_cache";
+    /// How long a cached avatar is used before it gets downloaded again
+    const TTL: Duration = Duration::from_secs(24 * 60 * 60);
+    /// How long an avatar is kept around after it was last downloaded
+    const MAX_AGE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+    pub fn open(storage: &Storage, max_size: u64) -> Result<Self, Error> {
+        let dir = storage.root().join(Self::DIR_NAME);
+        fs::create_dir_all(&dir)?;
+
+        Ok(Self {
+            dir,
+            max_size,
+            size: AtomicU64::new(0),
+        })
+    }
+
+    /// Gets the avatar with `hash`, if it was downloaded recently enough
+    pub fn get(&self, hash: &str) -> Option<Vec<u8>> {
+        let (bytes, age) = self.read(hash)?;
+        if age <= Self::TTL {
+            Some(bytes)
+        } else {
+            None
+        }
+    }
+
+    /// Gets the avatar with `hash` no matter how long ago it was downloaded
+    pub fn get_stale(&self, hash: &str) -> Option<Vec<u8>> {
+        self.read(hash).map(|(bytes, _)| bytes)
+    }
+
+    pub fn insert(&self, hash: &str, bytes: &[u8]) -> Result<(), Error> {
+        let path = self.path(hash).ok_or("Invalid avatar hash")?;
+        let tmp_path = path.with_extension("tmp");
+
+        fs::write(&tmp_path, bytes)?;
+        fs::rename(tmp_path, path)?;
+
+        let len = bytes.len() as u64;
+        if self.size.fetch_add(len, Ordering::Relaxed) + len > self.max_size {
+            self.prune()?;
+        }
+
+        Ok(())
+    }
+
+    /// Removes the avatars which weren't downloaded for so long they shouldn't be used anymore, and
+    /// then the least recently downloaded ones until the cache fits in its maximum size
+    pub fn prune(&self) -> Result<(), Error> {
+        let mut removed = 0;
+        let mut kept = vec![];
+
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            match age(&path) {
+                Ok(age) if age > Self::MAX_AGE => {
+                    fs::remove_file(&path)?;
+                    removed += 1;
+                }
+                Ok(age) => kept.push((age, fs::metadata(&path)?.len(), path)),
+                Err(err) => warn!("Could not check the age of {}: {}", path.display(), err),
+            }
+        }
+
+        // The oldest avatars end up last, where they're popped from
+        kept.sort_by_key(|(age, _, _)| *age);
+        let mut size = kept.iter().map(|(_, len, _)| len).sum::<u64>();
+        while size > self.max_size {
+            let (_, len, path) = match kept.pop() {
+                Some(entry) => entry,
+                None => break,
+            };
+            fs::remove_file(&path)?;
+            size -= len;
+            removed += 1;
+        }
+        self.size.store(size, Ordering::Relaxed);
+
+        info!("Removed {} old avatars from the cache", removed);
+
+        Ok(())
+    }
+
+    fn read(&self, hash: &str) -> Option<(Vec<u8>, Duration)> {
+        let path = self.path(hash)?;
+        let age = age(&path).ok()?;
+        let bytes = fs::read(&path).ok()?;
+
+        Some((bytes, age))
+    }
+
+    /// The path the avatar with `hash` is stored at
+    ///
+    /// Hashes are hex strings (prefixed with `a_` for animated avatars), anything else is rejected so
+    /// they can't be used to escape the cache's directory.
+    fn path(&self, hash: &str) -> Option<PathBuf> {
+        let valid = !hash.is_empty() && hash.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+
+        if valid {
+            Some(self.dir.join(format!("{}.webp", hash)))
+        } else {
+            None
+        }
+    }
+}
+
+fn age(path: &Path) -> Result<Duration, Error> {
+    let modified = fs::metadata(path)?.modified()?;
+    Ok(SystemTime::now()
+        .duration_since(modified)
+        .unwrap_or_default())
+}
+
+pub struct AvatarCacheContainer;
+
+impl TypeMapKey for AvatarCacheContainer {
+    type Value = Arc<AvatarCache>;
+}
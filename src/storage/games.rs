@@ -0,0 +1,109 @@
+use super::{Error, Storage};
+use crate::game::Game;
+
+use serde::{Deserialize, Serialize};
+use std::{fs, io::ErrorKind, path::PathBuf};
+use tracing::warn;
+
+/// A game as it's kept on disk
+#[derive(Serialize, Deserialize, Clone)]
+pub struct GameRecord {
+    pub game: Game,
+    /// Whether the game is over and everything made for it was cleaned up
+    pub finished: bool,
+}
+
+const GAMES_DIRECTORY: &str = "games";
+
+fn file_name(id: u64) -> String {
+    format!("{}/{}.json", GAMES_DIRECTORY, id)
+}
+
+/// Saves a snapshot of `game`
+pub fn save_game(storage: &Storage, game: &Game, finished: bool) -> Result<(), Error> {
+    storage.save(
+        &file_name(game.id()),
+        &GameRecord {
+            game: game.clone(),
+            finished,
+        },
+    )
+}
+
+/// Loads a single game, if it's kept on disk
+pub fn load_game(storage: &Storage, id: u64) -> Result<Option<GameRecord>, Error> {
+    match fs::read(storage.root().join(file_name(id))) {
+        Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Forgets a game, doing nothing if it isn't kept on disk
+pub fn delete_game(storage: &Storage, id: u64) -> Result<(), Error> {
+    match fs::remove_file(storage.root().join(file_name(id))) {
+        Err(err) if err.kind() != ErrorKind::NotFound => Err(err.into()),
+        _ => Ok(()),
+    }
+}
+
+/// Loads every game kept on disk, finished or not
+///
+/// Files that can't be read are skipped, so one broken game doesn't hold up all the others.
+pub fn all_games(storage: &Storage) -> Result<Vec<GameRecord>, Error> {
+    let mut games = vec![];
+    for path in game_files(storage)? {
+        let record = fs::read(&path)
+            .map_err(Error::from)
+            .and_then(|bytes| Ok(serde_json::from_slice::<GameRecord>(&bytes)?));
+        match record {
+            Ok(record) => games.push(record),
+            Err(err) => warn!("Skipping the unreadable game {}: {}", path.display(), err),
+        }
+    }
+
+    Ok(games)
+}
+
+/// Loads all the games that weren't finished, i.e. the ones that were running when the bot stopped
+pub fn unfinished_games(storage: &Storage) -> Result<Vec<GameRecord>, Error> {
+    Ok(all_games(storage)?
+        .into_iter()
+        .filter(|record| !record.finished)
+        .collect())
+}
+
+/// Rewrites every game kept on disk, returning the names of their files
+pub(super) fn migrate(storage: &Storage) -> Result<Vec<String>, Error> {
+    let mut migrated = vec![];
+    for path in game_files(storage)? {
+        let name = format!(
+            "{}/{}",
+            GAMES_DIRECTORY,
+            path.file_name().unwrap_or_default().to_string_lossy()
+        );
+        if storage.rewrite::<GameRecord>(&name)? {
+            migrated.push(name);
+        }
+    }
+
+    Ok(migrated)
+}
+
+fn game_files(storage: &Storage) -> Result<Vec<PathBuf>, Error> {
+    let directory = storage.root().join(GAMES_DIRECTORY);
+    if !directory.exists() {
+        return Ok(vec![]);
+    }
+
+    let mut files = vec![];
+    for entry in fs::read_dir(directory)? {
+        let path = entry?.path();
+        if path.extension().map_or(false, |ext| ext == "json") {
+            files.push(path);
+        }
+    }
+    files.sort();
+
+    Ok(files)
+}
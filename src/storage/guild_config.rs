@@ -0,0 +1,110 @@
+use super::{Error, Storage};
+use crate::{game::GameRules, i18n};
+
+use serde::{Deserialize, Serialize};
+use serenity::{
+    model::id::{ChannelId, GuildId, RoleId},
+    prelude::*,
+};
+use std::{collections::HashMap, sync::Arc};
+
+/// Per-guild settings, editable through `!config`
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct GuildConfig {
+    /// Overrides the prefix the bot was started with
+    pub prefix: Option<String>,
+    pub player_role: Option<RoleId>,
+    pub meeting_room: Option<ChannelId>,
+    pub announcement_channel: Option<ChannelId>,
+    /// A category, usually made by `!setup`, that the players' rooms get created in. It's kept
+    /// around between games
+    pub rooms_category: Option<ChannelId>,
+    /// The name of the category the players' rooms get created in when `rooms_category` isn't set
+    pub rooms_category_name: String,
+    pub delete_rooms_on_end: bool,
+    /// Whether to attach a picture of the game to `!gameinfo` and to block announcements
+    pub status_board: bool,
+    /// The locale used with players that didn't pick one with `!language`
+    pub locale: String,
+    pub rules: GameRules,
+}
+
+impl Default for GuildConfig {
+    fn default() -> Self {
+        Self {
+            prefix: None,
+            player_role: None,
+            meeting_room: None,
+            announcement_channel: None,
+            rooms_category: None,
+            rooms_category_name: "Rooms".to_string(),
+            delete_rooms_on_end: true,
+            status_board: false,
+            locale: i18n::FALLBACK_LOCALE.to_string(),
+            rules: GameRules::default(),
+        }
+    }
+}
+
+/// The configurations of all guilds the bot knows about
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct GuildConfigs {
+    guilds: HashMap<u64, GuildConfig>,
+    /// The configuration of guilds that were never configured, which has the default rules from
+    /// the bot's configuration file
+    #[serde(skip)]
+    defaults: GuildConfig,
+}
+
+impl GuildConfigs {
+    pub(super) const FILE_NAME: &'static str = "guild_configs.json";
+
+    pub fn load(storage: &Storage, defaults: GuildConfig) -> Result<Self, Error> {
+        let mut configs: Self = storage.load(Self::FILE_NAME)?;
+        configs.defaults = defaults;
+        Ok(configs)
+    }
+
+    /// Gets the configuration of `guild`, falling back to the defaults if it was never configured
+    pub fn get(&self, guild: GuildId) -> GuildConfig {
+        self.guilds
+            .get(&guild.0)
+            .cloned()
+            .unwrap_or_else(|| self.defaults.clone())
+    }
+
+    pub fn contains(&self, guild: GuildId) -> bool {
+        self.guilds.contains_key(&guild.0)
+    }
+
+    /// Forgets the configuration of `guild` and persists all configurations to disk
+    pub fn remove(&mut self, storage: &Storage, guild: GuildId) -> Result<(), Error> {
+        self.guilds.remove(&guild.0);
+        storage.save(Self::FILE_NAME, self)
+    }
+
+    /// What `!config reset` goes back to
+    pub fn defaults(&self) -> &GuildConfig {
+        &self.defaults
+    }
+
+    /// Changes the configuration of `guild` and persists all configurations to disk
+    pub fn update<F>(&mut self, storage: &Storage, guild: GuildId, f: F) -> Result<(), Error>
+    where
+        F: FnOnce(&mut GuildConfig),
+    {
+        let defaults = &self.defaults;
+        f(self
+            .guilds
+            .entry(guild.0)
+            .or_insert_with(|| defaults.clone()));
+        storage.save(Self::FILE_NAME, self)
+    }
+}
+
+pub struct GuildConfigContainer;
+
+impl TypeMapKey for GuildConfigContainer {
+    type Value = Arc<RwLock<GuildConfigs>>;
+}
@@ -0,0 +1,112 @@
+//! Data the bot keeps on disk across restarts
+//!
+//! Everything is stored as pretty-printed JSON files inside a single directory, which makes it easy
+//! for someone hosting the bot to inspect or back up.
+
+mod avatar_cache;
+mod games;
+mod guild_config;
+mod user_locales;
+
+pub use avatar_cache::{AvatarCache, AvatarCacheContainer};
+pub use games::{all_games, delete_game, load_game, save_game, unfinished_games, GameRecord};
+pub use guild_config::{GuildConfig, GuildConfigContainer, GuildConfigs};
+pub use user_locales::{UserLocaleContainer, UserLocales};
+
+use serde::{de::DeserializeOwned, Serialize};
+use serenity::prelude::TypeMapKey;
+use std::{
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tracing::info;
+
+pub type Error = Box<dyn std::error::Error + Send + Sync>;
+
+pub struct Storage {
+    root: PathBuf,
+}
+
+impl Storage {
+    /// Opens (and creates, if needed) the storage directory at `root`
+    pub fn open(root: impl AsRef<Path>) -> Result<Self, Error> {
+        let root = root.as_ref().to_path_buf();
+        fs::create_dir_all(&root)?;
+        info!("Using {} as the storage directory", root.display());
+
+        Ok(Self { root })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Loads `name` from storage, returning `T::default()` if it was never saved
+    pub fn load<T: DeserializeOwned + Default>(&self, name: &str) -> Result<T, Error> {
+        match fs::read(self.root.join(name)) {
+            Ok(bytes) => Ok(serde_json::from_slice(&bytes)?),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(T::default()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Saves `value` as `name`
+    ///
+    /// The data is first written to a temporary file which then gets renamed over the old one,
+    /// so a crash while saving can't leave a half-written file behind.
+    pub fn save<T: Serialize>(&self, name: &str, value: &T) -> Result<(), Error> {
+        let path = self.root.join(name);
+        let tmp_path = self.root.join(format!("{}.tmp", name));
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(&tmp_path, serde_json::to_vec_pretty(value)?)?;
+        fs::rename(tmp_path, path)?;
+
+        Ok(())
+    }
+
+    /// Loads `name` as a `T` and saves it back, returning whether there was anything to rewrite
+    pub fn rewrite<T: DeserializeOwned + Serialize>(&self, name: &str) -> Result<bool, Error> {
+        let bytes = match fs::read(self.root.join(name)) {
+            Ok(bytes) => bytes,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(false),
+            Err(err) => return Err(err.into()),
+        };
+
+        let value: T =
+            serde_json::from_slice(&bytes).map_err(|err| format!("{}: {}", name, err))?;
+        self.save(name, &value)?;
+
+        Ok(true)
+    }
+}
+
+/// Rewrites every file in storage in the format this version of the bot uses, returning the names
+/// of the files that were rewritten
+///
+/// Files are read the same way the bot reads them, so settings added since they were last saved
+/// get their default values written out, and files the bot couldn't read are reported.
+pub fn migrate(storage: &Storage) -> Result<Vec<String>, Error> {
+    let mut migrated = vec![];
+
+    if storage.rewrite::<GuildConfigs>(GuildConfigs::FILE_NAME)? {
+        migrated.push(GuildConfigs::FILE_NAME.to_string());
+    }
+    if storage.rewrite::<UserLocales>(UserLocales::FILE_NAME)? {
+        migrated.push(UserLocales::FILE_NAME.to_string());
+    }
+    migrated.extend(games::migrate(storage)?);
+
+    Ok(migrated)
+}
+
+pub struct StorageContainer;
+
+impl TypeMapKey for StorageContainer {
+    type Value = Arc<Storage>;
+}
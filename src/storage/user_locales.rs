@@ -0,0 +1,35 @@
+use super::{Error, Storage};
+
+use serde::{Deserialize, Serialize};
+use serenity::{model::id::UserId, prelude::*};
+use std::{collections::HashMap, sync::Arc};
+
+/// The locales users picked with `!language`
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct UserLocales {
+    users: HashMap<u64, String>,
+}
+
+impl UserLocales {
+    pub(super) const FILE_NAME: &'static str = "user_locales.json";
+
+    pub fn load(storage: &Storage) -> Result<Self, Error> {
+        storage.load(Self::FILE_NAME)
+    }
+
+    pub fn get(&self, user: UserId) -> Option<String> {
+        self.users.get(&user.0).cloned()
+    }
+
+    /// Sets the locale of `user` and persists all locales to disk
+    pub fn set(&mut self, storage: &Storage, user: UserId, locale: String) -> Result<(), Error> {
+        self.users.insert(user.0, locale);
+        storage.save(Self::FILE_NAME, self)
+    }
+}
+
+pub struct UserLocaleContainer;
+
+impl TypeMapKey for UserLocaleContainer {
+    type Value = Arc<RwLock<UserLocales>>;
+}
@@ -0,0 +1,83 @@
+//! Tells systemd how the bot is doing when it runs as a `Type=notify` service, see
+//! `kingdom-royale-maid.service`
+//!
+//! Everything here does nothing when the bot wasn't started by systemd, or isn't running on Linux.
+
+use serenity::client::bridge::gateway::ShardManager;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// Tells systemd the bot is connected to Discord
+pub fn ready() {
+    #[cfg(target_os = "linux")]
+    notify(&[
+        sd_notify::NotifyState::Ready,
+        sd_notify::NotifyState::Status("Connected to Discord"),
+    ]);
+}
+
+/// Tells systemd the bot is shutting down
+pub fn stopping() {
+    #[cfg(target_os = "linux")]
+    notify(&[sd_notify::NotifyState::Stopping]);
+}
+
+/// Tells systemd the bot is starting over, it's ready again once `ready` gets called
+pub fn reloading() {
+    #[cfg(target_os = "linux")]
+    notify(&[sd_notify::NotifyState::Reloading]);
+}
+
+/// Pings the systemd watchdog while every shard is connected to Discord, if the service has
+/// `WatchdogSec` set
+///
+/// Shards that stay disconnected for longer than that get the bot restarted by systemd.
+pub fn spawn_watchdog(shard_manager: Arc<Mutex<ShardManager>>) {
+    #[cfg(target_os = "linux")]
+    {
+        use serenity::gateway::ConnectionStage;
+        use std::time::Duration;
+        use tracing::info;
+
+        let mut usec = 0;
+        if !sd_notify::watchdog_enabled(false, &mut usec) {
+            return;
+        }
+
+        // systemd recommends pinging twice as often as it expects
+        let interval = Duration::from_micros(usec / 2);
+        info!("Pinging the systemd watchdog every {:?}", interval);
+
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(interval).await;
+
+                let manager = shard_manager.lock().await;
+                let runners = manager.runners.lock().await;
+                let disconnected = runners
+                    .iter()
+                    .filter(|(_, runner)| runner.stage != ConnectionStage::Connected)
+                    .map(|(id, runner)| format!("shard {} is {}", id.0, runner.stage))
+                    .collect::<Vec<_>>();
+
+                if disconnected.is_empty() {
+                    notify(&[sd_notify::NotifyState::Watchdog]);
+                } else {
+                    let status = format!("Not pinging the watchdog, {}", disconnected.join(", "));
+                    notify(&[sd_notify::NotifyState::Status(&status)]);
+                }
+            }
+        });
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    drop(shard_manager);
+}
+
+#[cfg(target_os = "linux")]
+fn notify(state: &[sd_notify::NotifyState]) {
+    // The environment has to stay around, as systemd gets told about the bot more than once
+    if let Err(err) = sd_notify::notify(false, state) {
+        tracing::warn!("Couldn't notify systemd: {}", err);
+    }
+}
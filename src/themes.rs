@@ -0,0 +1,213 @@
+//! Theme packs, which reskin the text and role images of the game
+//!
+//! A pack is a `theme.json` inside a directory named after the theme. It overrides any text from
+//! the catalogue (see [`crate::i18n`]), per locale, and can replace the role images. Packs get built
+//! into the bot from `res/themes`, and more can be put in the `themes` directory of the storage,
+//! where they take precedence over built-in packs with the same name.
+
+use crate::{
+    commands::game::GameContainer,
+    i18n::{self, FALLBACK_LOCALE},
+    storage::Storage,
+};
+
+use rust_embed::RustEmbed;
+use serde::Deserialize;
+use serenity::prelude::*;
+use std::{collections::BTreeMap, fmt::Display, fs, sync::Arc};
+use tracing::{info, warn};
+
+/// The theme of the book the game comes from, which is what the catalogue is written for
+pub const DEFAULT_THEME: &str = "utsuro";
+
+#[derive(RustEmbed)]
+#[folder = "res/themes"]
+struct BuiltinThemes;
+
+#[derive(Deserialize, Default, Debug)]
+#[serde(default)]
+pub struct Theme {
+    #[serde(skip)]
+    name: String,
+    pub description: String,
+    /// Text overriding the catalogue, by locale and then by key
+    text: BTreeMap<String, BTreeMap<String, String>>,
+    /// Thumbnails for the role embeds, by role (`king`, `the_double`, ...)
+    role_images: BTreeMap<String, String>,
+}
+
+impl Theme {
+    fn parse(name: &str, json: &[u8]) -> Result<Self, serde_json::Error> {
+        let mut theme = serde_json::from_slice::<Theme>(json)?;
+        theme.name = name.to_string();
+        Ok(theme)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn lookup(&self, locale: &str, key: &str) -> Option<&str> {
+        self.text
+            .get(locale)
+            .and_then(|text| text.get(key))
+            .map(String::as_str)
+    }
+
+    /// Gets the text for `key` in `locale`
+    ///
+    /// The theme's own text is preferred even when it's not translated to `locale`, as the
+    /// catalogue would describe a different setting.
+    pub fn tr(&self, locale: &str, key: &str) -> String {
+        self.lookup(locale, key)
+            .or_else(|| self.lookup(FALLBACK_LOCALE, key))
+            .map(str::to_string)
+            .unwrap_or_else(|| i18n::tr(locale, key))
+    }
+
+    /// Like [`Theme::tr`], but replaces every `{name}` with its value from `args`
+    pub fn tr_with(&self, locale: &str, key: &str, args: &[(&str, &dyn Display)]) -> String {
+        i18n::fill(self.tr(locale, key), args)
+    }
+
+    pub fn role_image(&self, role: &str) -> Option<&str> {
+        self.role_images.get(role).map(String::as_str)
+    }
+}
+
+/// All the themes the bot knows about
+pub struct Themes {
+    themes: BTreeMap<String, Arc<Theme>>,
+}
+
+impl Themes {
+    /// Loads the built-in themes and then the ones in `storage`
+    ///
+    /// Themes that fail to load are skipped, so one broken pack doesn't keep the bot from starting.
+    pub fn load(storage: &Storage) -> Self {
+        let mut themes = BTreeMap::new();
+
+        for path in BuiltinThemes::iter() {
+            let name = match path.strip_suffix("/theme.json") {
+                Some(name) => name,
+                None => continue,
+            };
+            let file = BuiltinThemes::get(&path).expect("iter only yields embedded files");
+
+            match Theme::parse(name, &file) {
+                Ok(theme) => {
+                    themes.insert(name.to_string(), Arc::new(theme));
+                }
+                Err(err) => warn!("The built-in theme {} is malformed: {}", name, err),
+            }
+        }
+
+        let dir = storage.root().join("themes");
+        if let Ok(entries) = fs::read_dir(&dir) {
+            for entry in entries.filter_map(Result::ok) {
+                let name = entry.file_name().to_string_lossy().to_string();
+                let theme = fs::read(entry.path().join("theme.json"))
+                    .map_err(|err| err.to_string())
+                    .and_then(|json| Theme::parse(&name, &json).map_err(|err| err.to_string()));
+
+                match theme {
+                    Ok(theme) => {
+                        info!("Loaded the theme {} from {}", name, dir.display());
+                        themes.insert(name, Arc::new(theme));
+                    }
+                    Err(err) => warn!("Couldn't load the theme {}: {}", name, err),
+                }
+            }
+        }
+
+        themes.entry(DEFAULT_THEME.to_string()).or_insert_with(|| {
+            Arc::new(Theme {
+                name: DEFAULT_THEME.to_string(),
+                ..Theme::default()
+            })
+        });
+
+        Self { themes }
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.themes.contains_key(name)
+    }
+
+    /// Gets the theme called `name`, falling back to the default theme if there's none
+    pub fn get(&self, name: &str) -> Arc<Theme> {
+        self.themes
+            .get(name)
+            .or_else(|| self.themes.get(DEFAULT_THEME))
+            .cloned()
+            .expect("the default theme is always loaded")
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Theme> {
+        self.themes.values().map(|theme| &**theme)
+    }
+}
+
+pub struct ThemeContainer;
+
+impl TypeMapKey for ThemeContainer {
+    type Value = Arc<Themes>;
+}
+
+/// Gets the theme called `name`
+pub async fn get(ctx: &Context, name: &str) -> Arc<Theme> {
+    ctx.data
+        .read()
+        .await
+        .get::<ThemeContainer>()
+        .expect("ctx.data should always have a ThemeContainer")
+        .get(name)
+}
+
+/// Gets the theme of the running game, or the default theme if there's no game
+///
+/// This locks the game, so it mustn't be called while holding its lock.
+pub async fn current(ctx: &Context) -> Arc<Theme> {
+    let game = ctx.data.read().await.get::<GameContainer>().cloned();
+    let name = match game {
+        Some(game) => game.read().await.theme().to_string(),
+        None => DEFAULT_THEME.to_string(),
+    };
+
+    get(ctx, &name).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn built_in_themes_only_override_known_text() {
+        for path in BuiltinThemes::iter() {
+            let name = path.trim_end_matches("/theme.json");
+            let theme = Theme::parse(name, &BuiltinThemes::get(&path).unwrap())
+                .unwrap_or_else(|err| panic!("{} is malformed: {}", name, err));
+
+            for (locale, text) in theme.text.iter() {
+                assert!(
+                    i18n::is_supported(locale),
+                    "{} has text for {}",
+                    name,
+                    locale
+                );
+
+                for (key, text) in text.iter() {
+                    let original = i18n::lookup(FALLBACK_LOCALE, key)
+                        .unwrap_or_else(|| panic!("{} overrides the unknown key {}", name, key));
+                    assert_eq!(
+                        i18n::placeholders(text),
+                        i18n::placeholders(original),
+                        "{} changes the placeholders of {}",
+                        name,
+                        key
+                    );
+                }
+            }
+        }
+    }
+}